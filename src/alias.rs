@@ -0,0 +1,87 @@
+//! In-house spellings of old-style macros, e.g. `PROJ_MOCK_METHODn`.
+
+use crate::parse::Variant;
+
+/// Macro that wraps an old-style gMock macro under another name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alias {
+    /// Spelling before and after the number of parameters.
+    prefix: String,
+    suffix: String,
+    /// The gMock macro it stands for.
+    pub variant: Variant,
+    /// New-style macro to convert to, e.g. `PROJ_MOCK_METHOD`.
+    pub target: String,
+}
+
+impl Alias {
+    /// `spelling` is the macro name with its only lowercase `n` standing for
+    /// the number of parameters, e.g. `PROJ_MOCK_CONST_METHODn_T`.
+    pub fn new(spelling: &str, variant: Variant, target: &str) -> Result<Self, String> {
+        let ident = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+        if !ident(spelling) || spelling.matches('n').count() != 1 {
+            return Err(format!("alias '{}' should be a macro name with one lowercase 'n' for the number of parameters", spelling))
+        }
+        if !ident(target) {
+            return Err(format!("alias target '{}' should be a macro name", target))
+        }
+
+        let (prefix, suffix) = spelling.split_once('n').unwrap();
+
+        Ok(Alias { prefix: prefix.to_owned(), suffix: suffix.to_owned(), variant, target: target.to_owned() })
+    }
+
+    /// Number of parameters, if `name` is this alias.
+    fn arity(&self, name: &str) -> Option<usize> {
+        let n = name.strip_prefix(&self.prefix)?.strip_suffix(&self.suffix)?;
+
+        match n {
+            "10" => Some(10),
+            _ if n.len() == 1 => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Variant, number of parameters and new-style macro of an old-style macro
+/// name. Canonical gMock names are always recognised.
+pub(crate) fn recognise<'a>(name: &str, aliases: &'a [Alias]) -> Option<(Variant, usize, &'a str)> {
+    if let Some((variant, arity)) = Variant::parse(name) {
+        return Some((variant, arity, "MOCK_METHOD"))
+    }
+
+    aliases.iter().find_map(|a| a.arity(name).map(|arity| (a.variant, arity, a.target.as_str())))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> Vec<Alias> {
+        let variant = Variant { is_const: true, is_template: true, with_calltype: false };
+        vec![Alias::new("PROJ_MOCK_CONST_METHODn_T", variant, "PROJ_MOCK_METHOD").unwrap()]
+    }
+
+    #[test]
+    fn test_recognise() {
+        let aliases = aliases();
+        let (variant, arity, target) = recognise("PROJ_MOCK_CONST_METHOD10_T", &aliases).unwrap();
+
+        assert_eq!((variant.to_string(), arity, target), ("MOCK_CONST_METHODn_T".to_owned(), 10, "PROJ_MOCK_METHOD"));
+        assert_eq!(recognise("MOCK_METHOD2", &aliases).map(|r| (r.1, r.2)), Some((2, "MOCK_METHOD")));
+        assert_eq!(recognise("PROJ_MOCK_CONST_METHOD_T", &aliases), None);
+        assert_eq!(recognise("PROJ_MOCK_CONST_METHOD12_T", &aliases), None);
+        assert_eq!(recognise("PROJ_MOCK_METHOD1", &aliases), None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let v = Variant::default();
+
+        assert!(Alias::new("PROJ_MOCK_METHOD", v, "MOCK_METHOD").is_err());
+        assert!(Alias::new("proj_mock_const_methodn", v, "MOCK_METHOD").is_err());
+        assert!(Alias::new("PROJ_MOCK_METHODn", v, "").is_err());
+    }
+}
@@ -1,4 +1,9 @@
 use std::path::PathBuf;
+use std::str::FromStr;
+use globset::Glob;
+use gmock_sed::Arity;
+
+use crate::config::Config;
 pub use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -6,38 +11,235 @@ pub use structopt::StructOpt;
     name = "gmock-sed",
     about = "Simple CLI tool for updating gMock macros. (MOCK_METHODn -> MOCK_METHOD)",
 )]
-pub enum Opt {
+pub struct Opt {
+    /// Settings file. Defaults to .gmock-sed.toml in the current directory, if present.
+    #[structopt(long, global = true, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    pub cmd: Command,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
     /// Locate files that contain at least 1 old-style MOCK_METHDODn macro.
     Search {
         /// List each result with a count of macros detected.
         #[structopt(short, long)]
         count: bool,
 
-        /// Specify the maximum depth for directory traversal.
-        #[structopt(long, default_value = "50")]
-        max_depth: usize,
+        /// List each macro as path:line:column: MACRO_NAME.
+        #[structopt(short = "n", long, conflicts_with = "count")]
+        line_numbers: bool,
+
+        /// Show NUM lines of context around each macro. Implies --line-numbers.
+        #[structopt(short = "C", long, name = "NUM", conflicts_with = "count")]
+        context: Option<usize>,
+
+        /// Output format: text, json, jsonl (one record per line, as files are searched) or sarif.
+        #[structopt(long, default_value = "text", possible_values = &["text", "json", "jsonl", "sarif"])]
+        format: Format,
+
+        /// Also match macros inside comments, string literals and directives.
+        #[structopt(long)]
+        include_comments: bool,
+
+        #[structopt(flatten)]
+        walk: WalkOpts,
 
         /// Top-level directory in which search for old-style MOCK_METHODn macros.
         #[structopt(name = "DIR", parse(from_os_str))]
         dir: PathBuf,
     },
 
+    /// Fail if any old-style MOCK_METHODn macros remain. Lists each offender.
+    ///
+    /// Exits with 1 if any remain, or 2 if some could not be converted or read.
+    Check {
+        /// Also match macros inside comments, string literals and directives.
+        #[structopt(long)]
+        include_comments: bool,
+
+        /// Output format: text or sarif.
+        #[structopt(long, default_value = "text", possible_values = &["text", "sarif"])]
+        format: Format,
+
+        /// What to do with a MOCK_METHODn that doesn't declare n parameters:
+        /// error (count it as unconvertible) or warn (ignore it).
+        #[structopt(long, default_value = "error", possible_values = &["error", "warn"])]
+        arity: Arity,
+
+        #[structopt(flatten)]
+        walk: WalkOpts,
+
+        /// Only fail for files with more macros than this baseline allows.
+        /// The baseline shrinks automatically as files get converted.
+        #[structopt(long, parse(from_os_str))]
+        baseline: Option<PathBuf>,
+
+        /// Top-level directory in which to check for old-style MOCK_METHODn macros.
+        #[structopt(name = "DIR", parse(from_os_str))]
+        dir: PathBuf,
+    },
+
+    /// Record the current per-file counts of old-style macros for `check --baseline`.
+    Baseline {
+        /// Also match macros inside comments, string literals and directives.
+        #[structopt(long)]
+        include_comments: bool,
+
+        #[structopt(flatten)]
+        walk: WalkOpts,
+
+        /// File to write the baseline to.
+        #[structopt(short, long, default_value = ".gmock-sed-baseline", parse(from_os_str))]
+        output: PathBuf,
+
+        /// Top-level directory in which to count old-style MOCK_METHODn macros.
+        #[structopt(name = "DIR", parse(from_os_str))]
+        dir: PathBuf,
+    },
+
     /// Substitute old-style macros with equivalent new-style macros.
     Replace {
         /// Don't overwrite files.
         #[structopt(long)]
         dry_run: bool,
 
+        /// Print a unified diff of the changes instead of writing files.
+        #[structopt(long)]
+        diff: bool,
+
+        /// When to use colours: auto, always or never.
+        #[structopt(long, default_value = "auto")]
+        color: ColorChoice,
+
+        /// Write the conversions to FILE as clang-tidy fixes YAML, for clang-apply-replacements.
+        /// Files are not changed.
+        #[structopt(long, name = "FILE", parse(from_os_str))]
+        export_fixes: Option<PathBuf>,
+
+        /// Output format: text, json or jsonl (one record per line).
+        #[structopt(long, default_value = "text", possible_values = &["text", "json", "jsonl"])]
+        format: Format,
+
         /// Show code that produced an error.
         #[structopt(long)]
         show_errors: bool,
 
+        /// What to do with a MOCK_METHODn that doesn't declare n parameters:
+        /// error (leave it unconverted) or warn (convert it anyway).
+        #[structopt(long, default_value = "error", possible_values = &["error", "warn"])]
+        arity: Arity,
+
         /// Add 'override' keyword to qualifiers
         #[structopt(long)]
         add_override: bool,
 
-        /// Paths to files that should be fixed.
+        /// Also replace macros inside comments, string literals and directives.
+        #[structopt(long)]
+        include_comments: bool,
+
+        /// Convert every C++ file found in directories, not just those `search` flags.
+        #[structopt(long)]
+        all_files: bool,
+
+        #[structopt(flatten)]
+        walk: WalkOpts,
+
+        /// Files, or directories to search for files, that should be fixed.
         #[structopt(name = "PATHS", parse(from_os_str))]
         files: Vec<PathBuf>,
     },
 }
+
+// Directory traversal options, flattened into the subcommands that walk directories.
+#[derive(Debug, StructOpt)]
+pub struct WalkOpts {
+    /// Specify the maximum depth for directory traversal.
+    #[structopt(long, default_value = "50")]
+    pub max_depth: usize,
+
+    /// Only visit files matching this glob, relative to the directory. Repeatable.
+    #[structopt(long, number_of_values = 1)]
+    pub include: Vec<Glob>,
+
+    /// Skip files matching this glob, relative to the directory. Repeatable.
+    #[structopt(long, number_of_values = 1)]
+    pub exclude: Vec<Glob>,
+
+    /// Don't honour .gitignore, .ignore and similar files.
+    #[structopt(long)]
+    pub no_ignore: bool,
+
+    /// Also visit hidden files and directories.
+    #[structopt(long)]
+    pub hidden: bool,
+
+    /// Also treat files with this extension as C++. Repeatable.
+    #[structopt(long, number_of_values = 1)]
+    pub ext: Vec<String>,
+
+    /// Look inside extension-less, .inl, .ipp and .tcc files for C++ code.
+    #[structopt(long)]
+    pub sniff: bool,
+}
+
+impl WalkOpts {
+    pub fn configure(&mut self, config: &Config) {
+        self.ext.extend(config.extensions.iter().cloned());
+        self.sniff |= config.sniff;
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn apply(self) {
+        match self {
+            ColorChoice::Auto => {},
+            ColorChoice::Always => colored::control::set_override(true),
+            ColorChoice::Never => colored::control::set_override(false),
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("invalid colour choice '{}' (auto, always, never)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    JsonLines,
+    Sarif,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::JsonLines),
+            "sarif" => Ok(Format::Sarif),
+            _ => Err(format!("invalid format '{}' (text, json, jsonl, sarif)", s)),
+        }
+    }
+}
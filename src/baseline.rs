@@ -0,0 +1,104 @@
+//! Per-file counts of tolerated old-style macros. Same format as `search --count`.
+//!
+//! Paths are relative to the walked directory, so `mocks` and `./mocks` agree.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use gmock_sed::GmockSedError;
+use crate::util;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline(BTreeMap<String, usize>);
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, GmockSedError> {
+        let mut counts = BTreeMap::new();
+
+        for (i, line) in util::read(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+
+            let (file, count) = line.rsplit_once(':')
+                .and_then(|(f, c)| c.parse::<usize>().ok().map(|c| (f, c)))
+                .ok_or_else(|| GmockSedError::BaselineError { path: path.to_path_buf(), line: i + 1 })?;
+
+            counts.insert(file.to_owned(), count);
+        }
+
+        Ok(Baseline(counts))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GmockSedError> {
+        std::fs::write(path, self.to_string()).map_err(|e| GmockSedError::io(path, &e))
+    }
+
+    /// Record a file's count. Files without old-style macros are left out.
+    pub fn insert(&mut self, file: &Path, count: usize) {
+        if count > 0 {
+            self.0.insert(file.to_string_lossy().into_owned(), count);
+        }
+    }
+
+    pub fn allowed(&self, file: &Path) -> usize {
+        self.0.get(file.to_string_lossy().as_ref()).copied().unwrap_or(0)
+    }
+
+    /// True if any file is below its baseline, so the baseline can shrink.
+    pub fn improved_by(&self, current: &Baseline) -> bool {
+        self.0.iter().any(|(f, &n)| current.0.get(f).copied().unwrap_or(0) < n)
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (file, count) in self.0.iter() {
+            writeln!(f, "{}:{}", file, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(counts: &[(&str, usize)]) -> Baseline {
+        let mut b = Baseline::default();
+        for (f, n) in counts { b.insert(Path::new(f), *n) }
+        b
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline");
+        let b = baseline(&[("mocks/b.h", 2), ("mocks/a:b.h", 1), ("mocks/c.h", 0)]);
+
+        b.save(&path).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "mocks/a:b.h:1\nmocks/b.h:2\n");
+        assert_eq!(Baseline::load(&path).unwrap(), b);
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline");
+        std::fs::write(&path, "# comment\nmocks/a.h:1\nmocks/b.h\n").unwrap();
+
+        assert_eq!(Baseline::load(&path), Err(GmockSedError::BaselineError { path, line: 3 }));
+    }
+
+    #[test]
+    fn test_improved_by() {
+        let b = baseline(&[("a.h", 2), ("b.h", 1)]);
+
+        assert!(!b.improved_by(&baseline(&[("a.h", 2), ("b.h", 1)])));
+        assert!(b.improved_by(&baseline(&[("a.h", 1), ("b.h", 1)])));
+        assert!(b.improved_by(&baseline(&[("a.h", 2)])));
+    }
+}
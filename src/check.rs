@@ -0,0 +1,122 @@
+use std::fmt;
+
+use crate::errors::GmockSedError;
+use crate::replace::{replace, ReplaceOptions, ReplaceSummary};
+
+/// Every old-style macro left in the source, and whether it could be converted.
+/// Taken from what `replace` would do with the same options, so the two always agree.
+pub fn check(source: &str, options: &ReplaceOptions) -> CheckSummary {
+    CheckSummary::from(&replace(source, options))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckSummary {
+    pub offenders: Vec<Offender>,
+}
+
+impl From<&ReplaceSummary> for CheckSummary {
+    fn from(summary: &ReplaceSummary) -> Self {
+        let converted = summary.converted().iter().map(|c| {
+            let o = &c.old;
+            (o.span.start, Offender { line: o.line, column: o.column, macro_name: o.macro_name.clone(), error: None })
+        });
+
+        let failed = summary.errors().iter().map(|e| {
+            let error = Some(e.error.clone());
+            (e.span.start, Offender { line: e.line, column: e.column, macro_name: e.macro_name.clone(), error })
+        });
+
+        // Keep source order
+        let mut offenders: Vec<(usize, Offender)> = converted.chain(failed).collect();
+        offenders.sort_by_key(|(start, _)| *start);
+
+        CheckSummary { offenders: offenders.into_iter().map(|(_, o)| o).collect() }
+    }
+}
+
+impl CheckSummary {
+    pub fn is_clean(&self) -> bool {
+        self.offenders.is_empty()
+    }
+
+    /// Number of macros that `replace` would fail to convert.
+    pub fn failures(&self) -> usize {
+        self.offenders.iter().filter(|o| o.error.is_some()).count()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Offender {
+    pub line: usize,
+    pub column: usize,
+    pub macro_name: String,
+    pub error: Option<GmockSedError>,
+}
+
+impl fmt::Display for Offender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error.as_ref() {
+            Some(e) => write!(f, ":{}: {} [{}]", self.line, self.macro_name, e.kind()),
+            None => write!(f, ":{}: {}", self.line, self.macro_name),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replace::Arity;
+
+    fn check(source: &str, arity: Arity) -> CheckSummary {
+        super::check(source, &ReplaceOptions { arity, ..Default::default() })
+    }
+
+    #[test]
+    fn test_clean() {
+        let cpp = "MOCK_METHOD(bool, Foo, (int));";
+
+        assert!(check(cpp, Arity::Error).is_clean());
+    }
+
+    #[test]
+    fn test_offenders() {
+        let cpp = "MOCK_METHOD1(Foo, bool(int));\n  MOCK_CONST_METHOD1(Bar);";
+        let summary = check(cpp, Arity::Error);
+
+        assert_eq!(summary.offenders, vec![
+            Offender { line: 1, column: 1, macro_name: "MOCK_METHOD1".to_owned(), error: None },
+            Offender {
+                line: 2,
+                column: 3,
+                macro_name: "MOCK_CONST_METHOD1".to_owned(),
+                error: Some(GmockSedError::UnparseableReturnType { span: 54..54, found: String::new() }),
+            },
+        ]);
+        assert_eq!(summary.failures(), 1);
+    }
+
+    #[test]
+    fn test_arity() {
+        let cpp = "MOCK_METHOD2(Foo, bool(int));";
+
+        assert_eq!(check(cpp, Arity::Error).failures(), 1);
+        assert_eq!(check(cpp, Arity::Warn).failures(), 0);
+    }
+
+    #[test]
+    fn test_divergent_branches() {
+        let cpp = "#ifdef _WIN32\nMOCK_METHOD0_WITH_CALLTYPE(__stdcall, Foo, int());\n#else\nMOCK_METHOD0(Foo);\n#endif";
+        let summary = check(cpp, Arity::Error);
+
+        assert_eq!(summary.failures(), 2);
+        assert!(matches!(summary.offenders[0].error, Some(GmockSedError::DivergentBranches { .. })));
+    }
+
+    #[test]
+    fn test_display() {
+        let o = Offender { line: 3, column: 1, macro_name: "MOCK_METHOD0".to_owned(), error: None };
+
+        assert_eq!(o.to_string(), ":3: MOCK_METHOD0");
+    }
+}
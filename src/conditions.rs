@@ -0,0 +1,152 @@
+//! Preprocessor conditionals around each macro, e.g. `#ifdef _WIN32`.
+
+use std::fmt;
+
+use crate::lexer::{tokenize, Scope, TokenKind};
+
+/// Branch of an #if group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    /// Directive that opens the group, e.g. `#ifdef _WIN32`.
+    pub group: String,
+    /// Directive that opens the branch: `group` itself, an #elif or an #else.
+    pub branch: String,
+    /// (group, branch) in source order, as directives can read the same.
+    id: (usize, usize),
+}
+
+impl Condition {
+    /// Same #if group, different branch.
+    pub fn is_sibling(&self, other: &Condition) -> bool {
+        self.id.0 == other.id.0 && self.id.1 != other.id.1
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.id.1 {
+            0 => write!(f, "{}", self.group),
+            _ => write!(f, "{} ({})", self.branch, self.group),
+        }
+    }
+}
+
+/// Conditions in effect throughout a source file.
+pub(crate) struct Conditions {
+    /// Byte offset where the stack changes, and the stack from there on. Outermost first.
+    changes: Vec<(usize, Vec<Condition>)>,
+}
+
+impl Conditions {
+    pub(crate) fn new(src: &str) -> Self {
+        let mut stack: Vec<Condition> = Vec::new();
+        let mut changes = Vec::new();
+        let mut groups = 0;
+
+        for t in tokenize(src, Scope::Code).iter().filter(|t| t.kind == TokenKind::Directive) {
+            let directive = normalize(t.text(src));
+            let keyword = directive[1..].split(|c: char| !c.is_alphanumeric()).next().unwrap_or_default();
+
+            match keyword {
+                "if" | "ifdef" | "ifndef" => {
+                    stack.push(Condition { group: directive.clone(), branch: directive, id: (groups, 0) });
+                    groups += 1;
+                },
+                "elif" | "elifdef" | "elifndef" | "else" => match stack.last_mut() {
+                    Some(c) => { c.branch = directive; c.id.1 += 1 },
+                    None => continue,
+                },
+                "endif" => if stack.pop().is_none() { continue },
+                _ => continue,
+            }
+
+            changes.push((t.span.end, stack.clone()));
+        }
+
+        Conditions { changes }
+    }
+
+    /// Stack of conditions at a byte offset. Outermost first.
+    pub(crate) fn at(&self, offset: usize) -> Vec<Condition> {
+        let i = self.changes.partition_point(|(from, _)| *from <= offset);
+
+        i.checked_sub(1).map_or_else(Vec::new, |i| self.changes[i].1.clone())
+    }
+}
+
+/// `#` and the directive, without comments, and with whitespace and line
+/// splices collapsed, e.g. `#ifdef _WIN32` for `# ifdef  _WIN32 // Windows`.
+fn normalize(directive: &str) -> String {
+    let body = &directive[1..];
+    let mut normalized = String::from("#");
+    let mut space = false;
+
+    for t in tokenize(body, Scope::Code) {
+        if t.is_trivia() {
+            space = true;
+            continue
+        }
+
+        if space && normalized.len() > 1 {
+            normalized.push(' ');
+        }
+        normalized.push_str(t.text(body));
+        space = false;
+    }
+
+    normalized
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CPP: &str = "\
+a
+#ifdef _WIN32 // Windows
+b
+#  if X
+c
+#  endif
+#else
+d
+#endif
+e
+";
+
+    fn stack(line: usize) -> Vec<Condition> {
+        let offset = match line {
+            1 => 0,
+            _ => CPP.match_indices('\n').nth(line - 2).unwrap().0 + 1,
+        };
+        Conditions::new(CPP).at(offset)
+    }
+
+    fn at(line: usize) -> Vec<String> {
+        stack(line).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_stack() {
+        assert_eq!(at(1), Vec::<String>::new());
+        assert_eq!(at(3), vec!["#ifdef _WIN32"]);
+        assert_eq!(at(5), vec!["#ifdef _WIN32", "#if X"]);
+        assert_eq!(at(8), vec!["#else (#ifdef _WIN32)"]);
+        assert_eq!(at(10), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_siblings() {
+        let (b, c, d) = (stack(3), stack(5), stack(8));
+
+        assert!(b[0].is_sibling(&d[0]));
+        assert!(!b[0].is_sibling(&c[0]));
+        assert!(!c[1].is_sibling(&d[0]));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("# if  defined(A) \\\n && B /* b */"), "#if defined(A) && B");
+    }
+}
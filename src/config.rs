@@ -0,0 +1,128 @@
+//! Settings file, `.gmock-sed.toml` by default.
+//!
+//! ```toml
+//! extensions = ["ipp", "cu"]
+//! sniff = true
+//!
+//! [[aliases]]
+//! spelling = "PROJ_MOCK_CONST_METHODn_T"
+//! variant = "MOCK_CONST_METHODn_T"
+//! target = "PROJ_MOCK_METHOD"
+//! ```
+
+use std::path::Path;
+use serde::{Deserialize, Deserializer};
+
+use gmock_sed::{Alias, GmockSedError};
+use crate::util;
+
+pub const DEFAULT_PATH: &str = ".gmock-sed.toml";
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Extra C++ file extensions.
+    pub extensions: Vec<String>,
+    /// Look inside files without a known extension.
+    pub sniff: bool,
+    /// In-house spellings of old-style macros.
+    #[serde(deserialize_with = "aliases")]
+    pub aliases: Vec<Alias>,
+}
+
+/// `[[aliases]]` entry as written.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AliasEntry {
+    spelling: String,
+    variant: String,
+    #[serde(default = "default_target")]
+    target: String,
+}
+
+fn default_target() -> String {
+    "MOCK_METHOD".to_owned()
+}
+
+fn aliases<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Alias>, D::Error> {
+    use serde::de::Error;
+
+    Vec::<AliasEntry>::deserialize(deserializer)?.into_iter().map(|a| {
+        let variant = a.variant.parse().map_err(D::Error::custom)?;
+        Alias::new(&a.spelling, variant, &a.target).map_err(D::Error::custom)
+    }).collect()
+}
+
+impl Config {
+    /// An explicit path must exist. The default path is optional.
+    pub fn load(path: Option<&Path>) -> Result<Self, GmockSedError> {
+        let (path, required) = match path {
+            Some(p) => (p, true),
+            None => (Path::new(DEFAULT_PATH), false),
+        };
+
+        if !required && !path.exists() {
+            return Ok(Config::default())
+        }
+
+        toml::from_str(&util::read(path)?).map_err(|e| GmockSedError::ConfigError {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "extensions = [\"ipp\"]\nsniff = true\n").unwrap();
+
+        let expected = Config { extensions: vec!["ipp".to_owned()], sniff: true, aliases: Vec::new() };
+        assert_eq!(Config::load(Some(&path)), Ok(expected));
+    }
+
+    #[test]
+    fn test_aliases() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[[aliases]]\nspelling = \"PROJ_MOCK_METHODn\"\nvariant = \"MOCK_METHODn\"\n").unwrap();
+
+        let expected = Alias::new("PROJ_MOCK_METHODn", Default::default(), "MOCK_METHOD").unwrap();
+        assert_eq!(Config::load(Some(&path)).map(|c| c.aliases), Ok(vec![expected]));
+    }
+
+    #[test]
+    fn test_invalid_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        for entry in ["spelling = \"PROJ_MOCK_METHOD\"\nvariant = \"MOCK_METHODn\"", "spelling = \"PROJ_MOCK_METHODn\"\nvariant = \"MOCK_FUNCTIONn\""] {
+            std::fs::write(&path, format!("[[aliases]]\n{}\n", entry)).unwrap();
+
+            assert!(matches!(Config::load(Some(&path)), Err(GmockSedError::ConfigError { .. })));
+        }
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "extension = [\"ipp\"]\n").unwrap();
+
+        assert!(matches!(Config::load(Some(&path)), Err(GmockSedError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn test_missing_explicit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.toml");
+
+        assert!(matches!(Config::load(Some(&path)), Err(GmockSedError::IoError { .. })));
+    }
+}
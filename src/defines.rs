@@ -0,0 +1,94 @@
+//! Old-style macros in #define bodies. Their `\` continuations are kept lined up.
+
+use std::ops::Range;
+
+use crate::lexer::{line, tokenize, Scope, TokenKind};
+
+/// Byte ranges of every #define, from the `#` to the end of its last line.
+pub(crate) fn defines(src: &str) -> Vec<Range<usize>> {
+    tokenize(src, Scope::Code).into_iter()
+        .filter(|t| t.kind == TokenKind::Directive && t.text(src)[1..].trim_start().starts_with("define"))
+        .map(|t| t.span.start..(t.span.start + line(&src[t.span.start..])))
+        .collect()
+}
+
+/// `new` with its continuations lined up again, if they were in `old`.
+/// Lines that grew push the backslashes right, as formatters do.
+pub(crate) fn realign(old: &str, new: &str) -> String {
+    let column = match aligned(old) {
+        Some(column) => column,
+        None => return new.to_owned(),
+    };
+
+    let lines: Vec<&str> = new.split('\n').collect();
+    let width = lines.iter()
+        .filter_map(|l| continued(l))
+        .map(|(code, _)| code.trim_end().chars().count() + 1)
+        .fold(column, usize::max);
+
+    lines.iter().map(|l| match continued(l) {
+        Some((code, cr)) => {
+            let code = code.trim_end();
+            format!("{}{}\\{}", code, " ".repeat(width - code.chars().count()), cr)
+        },
+        None => l.to_string(),
+    }).collect::<Vec<_>>().join("\n")
+}
+
+/// Column of the backslashes, if they all line up.
+fn aligned(s: &str) -> Option<usize> {
+    let columns: Vec<usize> = s.split('\n').filter_map(continued).map(|(code, _)| code.chars().count()).collect();
+
+    match columns.first() {
+        Some(&c) if columns.iter().all(|&x| x == c) => Some(c),
+        _ => None,
+    }
+}
+
+/// Code before the backslash of a continued line, and the `\r` of a CRLF line ending.
+fn continued(line: &str) -> Option<(&str, &str)> {
+    let (line, cr) = match line.strip_suffix('\r') {
+        Some(l) => (l, "\r"),
+        None => (line, ""),
+    };
+
+    line.strip_suffix('\\').map(|code| (code, cr))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defines() {
+        let src = "#if X\n#define A(x) \\\n  x\nint y;\n# define B\n";
+        let found: Vec<&str> = defines(src).into_iter().map(|r| &src[r]).collect();
+
+        assert_eq!(found, vec!["#define A(x) \\\n  x", "# define B"]);
+    }
+
+    #[test]
+    fn test_realign_shorter() {
+        let old = "#define A     \\\n  LONGER_LINE \\\n  x";
+        let new = "#define A     \\\n  SHORT \\\n  x";
+
+        assert_eq!(realign(old, new), "#define A     \\\n  SHORT       \\\n  x");
+    }
+
+    #[test]
+    fn test_realign_longer() {
+        let old = "#define A \\\r\n  B       \\\r\n  x";
+        let new = "#define A \\\r\n  B_IS_NOW_LONGER \\\r\n  x";
+
+        assert_eq!(realign(old, new), "#define A         \\\r\n  B_IS_NOW_LONGER \\\r\n  x");
+    }
+
+    #[test]
+    fn test_unaligned_left_alone() {
+        let old = "#define A \\\n  LONGER_LINE \\\n  x";
+        let new = "#define A \\\n  B \\\n  x";
+
+        assert_eq!(realign(old, new), new);
+    }
+}
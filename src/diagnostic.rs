@@ -0,0 +1,175 @@
+//! rustc-style diagnostics for macros that `replace` couldn't convert.
+//!
+//! ```text
+//! error[E0004]: unparseable return type
+//!  --> MockFoo.h:2:21
+//!   |
+//! 2 |   MOCK_METHOD1(Foo, bool);
+//!   |                     ^^^^ expected a function type, e.g. `bool(int)`, found `bool`
+//!   |
+//!   = help: the method's type is its return type followed by the parameter list, e.g. `bool(int)`
+//! ```
+
+use std::fmt::Write;
+use std::path::Path;
+use colored::*;
+
+use gmock_sed::{GmockSedError, ReplaceError};
+
+/// Title and general explanation of an error.
+fn describe(e: &GmockSedError) -> (&'static str, &'static str) {
+    use GmockSedError::*;
+    match e {
+        UnmatchedParenthesis { .. } => (
+            "unmatched parenthesis",
+            "every `(` in the macro needs a matching `)`",
+        ),
+        MissingMethodName { .. } => (
+            "missing method name",
+            "old-style macros take the method name, then its function type, e.g. `MOCK_METHOD1(Foo, bool(int))`",
+        ),
+        UnparseableReturnType { .. } => (
+            "unparseable return type",
+            "the method's type is its return type followed by the parameter list, e.g. `bool(int)`",
+        ),
+        ArityMismatch { .. } => (
+            "arity mismatch",
+            "the n in MOCK_METHODn must match the number of parameters",
+        ),
+        UnsupportedConstruct { .. } => (
+            "unsupported construct",
+            "this macro has to be converted by hand",
+        ),
+        DivergentBranches { .. } => (
+            "divergent branches",
+            "branches are converted together, so that every configuration builds the same mocks",
+        ),
+        _ => (e.kind(), ""),
+    }
+}
+
+/// 1-based line and column of `offset`, counted on from the start of the macro.
+fn position(source: &str, e: &ReplaceError, offset: usize) -> (usize, usize) {
+    let text = &source[e.span.start..offset];
+
+    match text.rfind('\n') {
+        Some(i) => (e.line + text.matches('\n').count(), text[(i + 1)..].chars().count() + 1),
+        None => (e.line, e.column + text.chars().count()),
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+pub fn render(path: &Path, source: &str, e: &ReplaceError, level: Level) -> String {
+    let (title, help) = describe(&e.error);
+    let paint = |s: &str| match level {
+        Level::Error => s.red().bold(),
+        Level::Warning => s.yellow().bold(),
+    };
+    let level = match level { Level::Error => "error", Level::Warning => "warning" };
+    let label = e.error.span().filter(|s| s.start >= e.span.start).unwrap_or_else(|| e.span.clone());
+    let (first, column) = position(source, e, label.start);
+    let (last, end) = position(source, e, label.end);
+
+    let width = last.to_string().len();
+    let gutter = " ".repeat(width);
+    let bar = "|".blue().bold();
+    let mut out = String::new();
+
+    #[allow(unused)]
+    {
+        writeln!(out, "{}: {}", paint(&format!("{}[{}]", level, e.error.code())), title.bold());
+        writeln!(out, "{}{} {}:{}:{}", gutter, "-->".blue().bold(), path.display(), first, column);
+        writeln!(out, "{} {}", gutter, bar);
+
+        for (n, text) in source.lines().enumerate().map(|(i, l)| (i + 1, l)).skip(first - 1).take(last - first + 1) {
+            let from = if n == first { column - 1 } else { text.chars().take_while(|c| c.is_whitespace()).count() };
+            let to = if n == last { end - 1 } else { text.chars().count() };
+            let note = if n == last { format!(" {}", e.error) } else { String::new() };
+
+            // Keep tabs so that the carets line up
+            let indent: String = text.chars().take(from).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+            let carets = "^".repeat(to.saturating_sub(from).max(1));
+
+            writeln!(out, "{} {} {}", format!("{:>w$}", n, w = width).blue().bold(), bar, text);
+            writeln!(out, "{} {} {}{}{}", gutter, bar, indent, paint(&carets), paint(&note));
+        }
+
+        writeln!(out, "{} {}", gutter, bar);
+
+        if !help.is_empty() {
+            writeln!(out, "{} {} {}: {}", gutter, "=".blue().bold(), "help".bold(), help);
+        }
+    }
+
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gmock_sed::{Arity, ReplaceOptions};
+
+    fn render_all(source: &str) -> String {
+        colored::control::set_override(false);
+
+        let summary = gmock_sed::replace(source, &ReplaceOptions::default());
+        summary.errors().iter().map(|e| render(Path::new("a.h"), source, e, Level::Error)).collect()
+    }
+
+    #[test]
+    fn test_caret() {
+        let expected = "\
+error[E0004]: unparseable return type
+ --> a.h:2:21
+  |
+2 |   MOCK_METHOD1(Foo, bool);
+  |                     ^^^^ expected a function type, e.g. `bool(int)`, found `bool`
+  |
+  = help: the method's type is its return type followed by the parameter list, e.g. `bool(int)`
+";
+        assert_eq!(render_all("class A {\n  MOCK_METHOD1(Foo, bool);\n};"), expected);
+    }
+
+    #[test]
+    fn test_multiline_label() {
+        let expected = "\
+error[E0004]: unparseable return type
+  --> a.h:9:19
+   |
+ 9 | MOCK_METHOD1(Foo, bool
+   |                   ^^^^
+10 |     const);
+   |     ^^^^^ expected a function type, e.g. `bool(int)`, found `bool const`
+   |
+";
+        let rendered = render_all("\n\n\n\n\n\n\n\nMOCK_METHOD1(Foo, bool\n    const);");
+
+        assert!(rendered.starts_with(expected), "{}", rendered);
+    }
+
+    #[test]
+    fn test_unmatched_parenthesis() {
+        let rendered = render_all("\tMOCK_METHOD1(Foo, bool(int);");
+
+        assert!(rendered.starts_with("error[E0002]: unmatched parenthesis\n --> a.h:1:14\n"), "{}", rendered);
+        assert!(rendered.contains("\n  | \t            ^ this parenthesis is never closed\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_warning() {
+        colored::control::set_override(false);
+
+        let source = "MOCK_METHOD2(Foo, bool(int));";
+        let summary = gmock_sed::replace(source, &ReplaceOptions { arity: Arity::Warn, ..Default::default() });
+        let rendered = render(Path::new("a.h"), source, &summary.warnings()[0], Level::Warning);
+
+        assert!(rendered.starts_with("warning[E0005]: arity mismatch\n --> a.h:1:24\n"), "{}", rendered);
+        assert!(rendered.contains("^^^ expected 2 parameter(s), found 1\n"), "{}", rendered);
+    }
+}
@@ -0,0 +1,43 @@
+//! Unified diff of a file against its suggested replacement. Compatible with `git apply`.
+
+use std::path::Path;
+use colored::*;
+use similar::TextDiff;
+
+use crate::util;
+
+pub fn unified(path: &Path, old: &str, new: &str) -> String {
+    // `git apply` wants paths relative to where it runs. Files outside that
+    // directory climb out with `..`, which it only takes with --unsafe-paths
+    let path = util::relative(path, Path::new(".")).to_string_lossy().replace('\\', "/");
+    let (a, b) = (format!("a/{}", path), format!("b/{}", path));
+
+    let patch = TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&a, &b)
+        .to_string();
+
+    patch.split_inclusive('\n').map(colorize).collect()
+}
+
+fn colorize(line: &str) -> String {
+    let (text, eol) = match line.strip_suffix('\n') {
+        Some(text) => (text, "\n"),
+        None => (line, ""),
+    };
+
+    let text = if text.starts_with("---") || text.starts_with("+++") {
+        text.bold()
+    } else if text.starts_with("@@") {
+        text.cyan()
+    } else if text.starts_with('-') {
+        text.red()
+    } else if text.starts_with('+') {
+        text.green()
+    } else {
+        text.normal()
+    };
+
+    format!("{}{}", text, eol)
+}
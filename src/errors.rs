@@ -1,15 +1,114 @@
 use std::error;
 use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, PartialEq)]
+/// Everything that can go wrong. Spans are byte ranges of the part of the
+/// source at fault, which may be empty if something is missing.
+#[derive(Debug, Clone, PartialEq)]
 pub enum GmockSedError {
-    ParseSignatureError,
-    UnmatchedParenthesisError,
+    /// `(` that is never closed, or `)` that is never opened.
+    UnmatchedParenthesis { span: Range<usize>, paren: char },
+    /// No method name before the function type, e.g. `MOCK_METHOD1(, bool(int))`.
+    MissingMethodName { span: Range<usize> },
+    /// The method's type isn't `Return(Args)`, e.g. `MOCK_METHOD1(Foo, bool)`.
+    UnparseableReturnType { span: Range<usize>, found: String },
+    /// `MOCK_METHODn` declares other than n parameters.
+    ArityMismatch { span: Range<usize>, expected: usize, found: usize },
+    /// Valid C++ that can't be converted, e.g. the macro name without arguments.
+    UnsupportedConstruct { span: Range<usize>, construct: &'static str },
+    /// Convertible, but a macro in another branch of the same #if isn't.
+    /// `sibling` is that macro.
+    DivergentBranches { span: Range<usize>, sibling: Range<usize> },
+    /// `message` is the OS error, e.g. "No such file or directory (os error 2)".
+    IoError { path: PathBuf, message: String },
+    EncodingError { path: PathBuf },
+    BaselineError { path: PathBuf, line: usize },
+    ConfigError { path: PathBuf, message: String },
+}
+
+impl GmockSedError {
+    /// Attach the offending path to an I/O error.
+    pub fn io(path: &Path, e: &io::Error) -> Self {
+        let path = path.to_path_buf();
+
+        match e.kind() {
+            io::ErrorKind::InvalidData => GmockSedError::EncodingError { path },
+            _ => GmockSedError::IoError { path, message: e.to_string() },
+        }
+    }
+
+    /// Stable code, e.g. `E0004`, for diagnostics and documentation.
+    pub fn code(&self) -> &'static str {
+        use GmockSedError::*;
+        match self {
+            UnmatchedParenthesis { .. } => "E0002",
+            MissingMethodName { .. } => "E0003",
+            UnparseableReturnType { .. } => "E0004",
+            ArityMismatch { .. } => "E0005",
+            UnsupportedConstruct { .. } => "E0006",
+            DivergentBranches { .. } => "E0007",
+            IoError { .. } => "E0101",
+            EncodingError { .. } => "E0102",
+            BaselineError { .. } => "E0201",
+            ConfigError { .. } => "E0202",
+        }
+    }
+
+    /// Variant name, stable enough for scripts to match on.
+    pub fn kind(&self) -> &'static str {
+        use GmockSedError::*;
+        match self {
+            UnmatchedParenthesis { .. } => "UnmatchedParenthesis",
+            MissingMethodName { .. } => "MissingMethodName",
+            UnparseableReturnType { .. } => "UnparseableReturnType",
+            ArityMismatch { .. } => "ArityMismatch",
+            UnsupportedConstruct { .. } => "UnsupportedConstruct",
+            DivergentBranches { .. } => "DivergentBranches",
+            IoError { .. } => "IoError",
+            EncodingError { .. } => "EncodingError",
+            BaselineError { .. } => "BaselineError",
+            ConfigError { .. } => "ConfigError",
+        }
+    }
+
+    /// Part of the source at fault. `None` for errors about whole files.
+    pub fn span(&self) -> Option<Range<usize>> {
+        use GmockSedError::*;
+        match self {
+            UnmatchedParenthesis { span, .. }
+            | MissingMethodName { span }
+            | UnparseableReturnType { span, .. }
+            | ArityMismatch { span, .. }
+            | UnsupportedConstruct { span, .. }
+            | DivergentBranches { span, .. } => Some(span.clone()),
+            IoError { .. } | EncodingError { .. } | BaselineError { .. } | ConfigError { .. } => None,
+        }
+    }
 }
 
 impl fmt::Display for GmockSedError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        use GmockSedError::*;
+        match self {
+            UnmatchedParenthesis { paren: '(', .. } => write!(f, "this parenthesis is never closed"),
+            UnmatchedParenthesis { .. } => write!(f, "this parenthesis is never opened"),
+            MissingMethodName { .. } => write!(f, "expected a method name"),
+            UnparseableReturnType { found, .. } if found.is_empty() => write!(f, "expected a function type, e.g. `bool(int)`"),
+            UnparseableReturnType { found, .. } => {
+                // Keep the message on one line, however the type was laid out
+                let found = found.split_whitespace().collect::<Vec<_>>().join(" ");
+                write!(f, "expected a function type, e.g. `bool(int)`, found `{}`", found)
+            },
+            ArityMismatch { expected, found, .. } => write!(f, "expected {} parameter(s), found {}", expected, found),
+            UnsupportedConstruct { construct, .. } => write!(f, "unsupported: {}", construct),
+            DivergentBranches { .. } => write!(f, "another branch of this #if can't be converted"),
+            IoError { path, message } => write!(f, "{}: {}", path.display(), message),
+            EncodingError { path } => write!(f, "{}: stream did not contain valid UTF-8", path.display()),
+            BaselineError { path, line } => write!(f, "{}:{}: expected 'path:count'", path.display(), line),
+            ConfigError { path, message } => write!(f, "{}: {}", path.display(), message),
+        }
     }
 }
 
@@ -1,66 +1,86 @@
-/// Extract substring from matching parentheses.
+//! Extract tokens from matching parentheses.
+
+use std::ops::Range;
 
 use crate::errors::GmockSedError;
+use crate::lexer::Token;
 
 type Result<T> = std::result::Result<T, GmockSedError>;
 
-pub fn lextract(s: &str) -> Result<&str> {
-    assert!(s.starts_with('('));
+/// Indices of the tokens inside the parentheses opened by the first token.
+pub fn lextract(tokens: &[Token]) -> Result<Range<usize>> {
+    assert!(tokens[0].is_punct('('));
     let mut n = 0;
 
-    for (i, c) in s.chars().enumerate() {
-        match c {
-            '(' => n += 1,
-            ')' => n -= 1,
-            _ => {},
-        }
+    for (i, t) in tokens.iter().enumerate() {
+        if t.is_punct('(') { n += 1 }
+        if t.is_punct(')') { n -= 1 }
 
-        if n == 0 { return Ok(&s[1..i]) }
+        if n == 0 { return Ok(1..i) }
     }
 
-    Err(GmockSedError::UnmatchedParenthesisError)
+    Err(GmockSedError::UnmatchedParenthesis { span: tokens[0].span.clone(), paren: '(' })
 }
 
-pub fn rextract(s: &str) -> Result<&str> {
-    assert!(s.ends_with(')'));
+/// Indices of the tokens inside the parentheses closed by the last token.
+pub fn rextract(tokens: &[Token]) -> Result<Range<usize>> {
+    assert!(tokens[tokens.len() - 1].is_punct(')'));
     let mut n = 0;
 
-    for (i, c) in s.chars().rev().enumerate() {
-        match c {
-            ')' => n += 1,
-            '(' => n -= 1,
-            _ => {},
-        }
+    for (i, t) in tokens.iter().enumerate().rev() {
+        if t.is_punct(')') { n += 1 }
+        if t.is_punct('(') { n -= 1 }
 
-        if n == 0 { return Ok(&s[(s.len() - i)..(s.len() - 1)]) }
+        if n == 0 { return Ok((i + 1)..(tokens.len() - 1)) }
     }
 
-    Err(GmockSedError::UnmatchedParenthesisError)
+    Err(GmockSedError::UnmatchedParenthesis { span: tokens[tokens.len() - 1].span.clone(), paren: ')' })
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lexer::{tokenize, Scope};
     const DEMO: &str = "(abc(df))(xy)";
 
+    fn text(src: &str, r: Range<usize>) -> String {
+        tokenize(src, Scope::Code)[r].iter().map(|t| t.text(src)).collect()
+    }
+
     #[test]
     fn test_lextract() {
-        assert_eq!(lextract(DEMO), Ok("abc(df)"));
+        let r = lextract(&tokenize(DEMO, Scope::Code)).unwrap();
+
+        assert_eq!(text(DEMO, r), "abc(df)");
     }
 
     #[test]
     fn test_lextract_unmatched() {
-        assert_eq!(lextract("(abc(df)"), Err(GmockSedError::UnmatchedParenthesisError));
+        let expected = GmockSedError::UnmatchedParenthesis { span: 0..1, paren: '(' };
+
+        assert_eq!(lextract(&tokenize("(abc(df)", Scope::Code)), Err(expected));
     }
 
     #[test]
     fn test_rextract() {
-        assert_eq!(rextract(DEMO), Ok("xy"));
+        let r = rextract(&tokenize(DEMO, Scope::Code)).unwrap();
+
+        assert_eq!(text(DEMO, r), "xy");
     }
 
     #[test]
     fn test_rextract_unmatched() {
-        assert_eq!(rextract("xy)"), Err(GmockSedError::UnmatchedParenthesisError));
+        let expected = GmockSedError::UnmatchedParenthesis { span: 2..3, paren: ')' };
+
+        assert_eq!(rextract(&tokenize("xy)", Scope::Code)), Err(expected));
+    }
+
+    #[test]
+    fn test_lextract_ignores_literals() {
+        let src = "(a(\")\"), ')')b";
+        let r = lextract(&tokenize(src, Scope::Code)).unwrap();
+
+        assert_eq!(text(src, r), "a(\")\"), ')'");
     }
 }
@@ -0,0 +1,81 @@
+//! Conversions as clang-tidy `--export-fixes` YAML, for `clang-apply-replacements`.
+
+use std::path::Path;
+use serde::Serialize;
+
+use gmock_sed::GmockSedError;
+use crate::outcome::FileOutcome;
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Fixes {
+    main_source_file: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Diagnostic {
+    diagnostic_name: &'static str,
+    diagnostic_message: Message,
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Message {
+    message: String,
+    file_path: String,
+    file_offset: usize,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Replacement {
+    file_path: String,
+    offset: usize,
+    length: usize,
+    replacement_text: String,
+}
+
+/// Write a replacement for every macro in files that `replace` would rewrite.
+/// Offsets and lengths are in bytes.
+pub fn export(path: &Path, outcomes: &[FileOutcome]) -> Result<(), GmockSedError> {
+    let diagnostics = outcomes.iter()
+        .filter_map(|o| o.summary.as_ref().filter(|r| r.error_free()).map(|r| (o.path, r)))
+        .flat_map(|(file, r)| {
+            // clang-apply-replacements groups by path, so make them unambiguous
+            let file = std::fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf()).to_string_lossy().into_owned();
+
+            // Macros in a realigned #define share one replacement, carried by the first
+            r.edits().iter().flat_map(move |e| {
+                let file = file.clone();
+
+                r.converted()[e.conversions.clone()].iter().enumerate().map(move |(i, c)| Diagnostic {
+                    diagnostic_name: "gmock-sed-mock-method",
+                    diagnostic_message: Message {
+                        message: format!("{} can be replaced with {}", c.old.macro_name, c.old.target),
+                        file_path: file.clone(),
+                        file_offset: c.old.span.start,
+                        replacements: match i {
+                            0 => vec![Replacement {
+                                file_path: file.clone(),
+                                offset: e.span.start,
+                                length: e.span.len(),
+                                replacement_text: e.text.clone(),
+                            }],
+                            _ => Vec::new(),
+                        },
+                    },
+                    level: "Warning",
+                })
+            })
+        })
+        .collect();
+
+    let fixes = Fixes { main_source_file: String::new(), diagnostics };
+    let yaml = serde_yaml::to_string(&fixes).expect("fixes only contain strings and numbers");
+
+    std::fs::write(path, format!("---\n{}...\n", yaml)).map_err(|e| GmockSedError::io(path, &e))
+}
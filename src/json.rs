@@ -0,0 +1,165 @@
+//! Machine-readable records for `--format json` and `--format jsonl`.
+//!
+//! Every file gets one record. `jsonl` prints each on a single line, `json`
+//! wraps them all in an array once all files are done.
+
+use std::ops::Range;
+use std::path::Path;
+use serde::Serialize;
+use serde_json::Value;
+
+use gmock_sed::{Conversion, GmockSedError, ReplaceError, SearchMatch, SearchSummary};
+use crate::outcome::{FileOutcome, ReadStatus, WriteStatus};
+
+#[derive(Serialize)]
+struct Occurrence<'a> {
+    macro_name: &'a str,
+    variant: String,
+    line: usize,
+    column: usize,
+    span: Range<usize>,
+    /// Only recorded by `search`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conditions: Option<Vec<Branch<'a>>>,
+}
+
+/// Enclosing #if branch, outermost first.
+#[derive(Serialize)]
+struct Branch<'a> {
+    group: &'a str,
+    branch: &'a str,
+}
+
+impl<'a> From<&'a SearchMatch> for Occurrence<'a> {
+    fn from(m: &'a SearchMatch) -> Self {
+        let conditions = m.conditions.iter().map(|c| Branch { group: &c.group, branch: &c.branch }).collect();
+
+        Occurrence {
+            macro_name: &m.macro_name,
+            variant: m.variant.to_string(),
+            line: m.line,
+            column: m.column,
+            span: m.span.clone(),
+            conditions: Some(conditions),
+        }
+    }
+}
+
+impl<'a> From<&'a Conversion> for Occurrence<'a> {
+    fn from(c: &'a Conversion) -> Self {
+        let m = &c.old;
+        Occurrence { macro_name: &m.macro_name, variant: m.variant.to_string(), line: m.line, column: m.column, span: m.span.clone(), conditions: None }
+    }
+}
+
+#[derive(Serialize)]
+struct Error<'a> {
+    kind: &'static str,
+    code: &'static str,
+    message: String,
+    line: Option<usize>,
+    column: Option<usize>,
+    span: Option<Range<usize>>,
+    snippet: Option<&'a str>,
+}
+
+impl<'a> From<&'a ReplaceError> for Error<'a> {
+    fn from(e: &'a ReplaceError) -> Self {
+        Error {
+            kind: e.error.kind(),
+            code: e.error.code(),
+            message: e.error.to_string(),
+            line: Some(e.line),
+            column: Some(e.column),
+            span: Some(e.span.clone()),
+            snippet: Some(&e.snippet),
+        }
+    }
+}
+
+impl<'a> From<&'a GmockSedError> for Error<'a> {
+    fn from(e: &'a GmockSedError) -> Self {
+        Error { kind: e.kind(), code: e.code(), message: e.to_string(), line: None, column: None, span: None, snippet: None }
+    }
+}
+
+#[derive(Serialize)]
+struct SearchRecord<'a> {
+    path: &'a str,
+    count: usize,
+    occurrences: Vec<Occurrence<'a>>,
+}
+
+#[derive(Serialize)]
+struct ReplaceRecord<'a> {
+    path: &'a str,
+    status: &'static str,
+    converted: usize,
+    failed: usize,
+    occurrences: Vec<Occurrence<'a>>,
+    errors: Vec<Error<'a>>,
+    warnings: Vec<Error<'a>>,
+}
+
+/// Record of a file searched with `SearchMode::Positions`.
+pub fn search(path: &Path, summary: &SearchSummary) -> Value {
+    let path = path.to_string_lossy();
+
+    record(&SearchRecord {
+        path: &path,
+        count: summary.matches.len(),
+        occurrences: summary.matches.iter().map(Occurrence::from).collect(),
+    })
+}
+
+/// Record of a file after `replace`, including why it was or wasn't written.
+pub fn replace(outcome: &FileOutcome) -> Value {
+    let path = outcome.path.to_string_lossy();
+    let summary = outcome.summary.as_ref();
+
+    let mut errors: Vec<Error> = summary.map_or(Vec::new(), |r| r.errors().iter().map(Error::from).collect());
+    errors.extend(outcome.io_error().map(Error::from));
+
+    record(&ReplaceRecord {
+        path: &path,
+        status: status(outcome),
+        converted: summary.map_or(0, |r| r.converted().len()),
+        failed: summary.map_or(0, |r| r.error_count()),
+        occurrences: summary.map_or(Vec::new(), |r| r.converted().iter().map(Occurrence::from).collect()),
+        errors,
+        warnings: summary.map_or(Vec::new(), |r| r.warnings().iter().map(Error::from).collect()),
+    })
+}
+
+fn record<T: Serialize>(r: &T) -> Value {
+    serde_json::to_value(r).expect("records only contain strings and numbers")
+}
+
+fn status(outcome: &FileOutcome) -> &'static str {
+    match (&outcome.read, &outcome.write) {
+        (ReadStatus::Empty, _) => "empty",
+        (ReadStatus::Failed(_), _) => "unreadable",
+        (_, WriteStatus::Pending) => "pending",
+        (_, WriteStatus::Written) => "written",
+        (_, WriteStatus::DryRun) => "dry-run",
+        (_, WriteStatus::Unchanged) => "unchanged",
+        (_, WriteStatus::Skipped) => "skipped",
+        (_, WriteStatus::Failed(_)) => "unwritable",
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gmock_sed::{SearchMode, SearchOptions};
+
+    #[test]
+    fn test_search_record() {
+        let cpp = "MOCK_CONST_METHOD0(Foo, int());";
+        let summary = gmock_sed::search(cpp, SearchMode::Positions, &SearchOptions::default());
+        let expected = r#"{"path":"a.h","count":1,"occurrences":[{"macro_name":"MOCK_CONST_METHOD0","variant":"MOCK_CONST_METHODn","line":1,"column":1,"span":{"start":0,"end":18},"conditions":[]}]}"#;
+
+        assert_eq!(search(Path::new("a.h"), &summary).to_string(), expected);
+    }
+}
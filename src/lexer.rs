@@ -0,0 +1,367 @@
+//! Minimal C++ lexer. Just enough to find macro boundaries.
+//!
+//! Every byte of the source belongs to exactly one token, so spans can be used
+//! to splice replacements back into the original text.
+
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    Comment,
+    Ident,
+    Number,
+    Str,
+    Char,
+    Directive,
+    Punct(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+impl Token {
+    pub fn text<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.span.clone()]
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Whitespace | TokenKind::Comment)
+    }
+
+    pub fn is_punct(&self, c: char) -> bool {
+        self.kind == TokenKind::Punct(c)
+    }
+}
+
+/// Which regions of the source are searched for macros.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Scope {
+    /// Skip comments, literals and preprocessor directives.
+    #[default]
+    Code,
+    /// Treat everything as code. Finds macros in commented-out code too.
+    All,
+}
+
+impl From<bool> for Scope {
+    fn from(include_comments: bool) -> Self {
+        if include_comments { Scope::All } else { Scope::Code }
+    }
+}
+
+pub fn tokenize(src: &str, scope: Scope) -> Vec<Token> {
+    Lexer::new(src, scope).collect()
+}
+
+pub struct Lexer<'a> {
+    src: &'a str,
+    scope: Scope,
+    pos: usize,
+    line_start: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(src: &'a str, scope: Scope) -> Self {
+        Lexer { src, scope, pos: 0, line_start: true }
+    }
+
+    fn lex(&self, rest: &str) -> (TokenKind, usize) {
+        use TokenKind::*;
+        let c = rest.chars().next().unwrap();
+
+        if c.is_whitespace() || splice(rest) > 0 {
+            (Whitespace, whitespace(rest))
+        } else if self.scope == Scope::All {
+            self.lex_all(rest, c)
+        } else if rest.starts_with("//") {
+            (Comment, line(rest))
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            (Comment, body.find("*/").map_or(rest.len(), |i| i + 4))
+        } else if c == '#' && self.line_start {
+            // Only the head of a #define, its body is code
+            (Directive, define(rest).unwrap_or_else(|| line(rest)))
+        } else if is_ident_start(c) {
+            let n = rest.find(|c| !is_ident_continue(c)).unwrap_or(rest.len());
+            literal(&rest[..n], &rest[n..]).unwrap_or((Ident, n))
+        } else if c.is_ascii_digit() || (c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+            (Number, number(rest))
+        } else if c == '"' {
+            (Str, quoted(rest, '"'))
+        } else if c == '\'' {
+            (Char, quoted(rest, '\''))
+        } else {
+            (Punct(c), c.len_utf8())
+        }
+    }
+
+    fn lex_all(&self, rest: &str, c: char) -> (TokenKind, usize) {
+        use TokenKind::*;
+
+        if is_ident_start(c) {
+            (Ident, rest.find(|c| !is_ident_continue(c)).unwrap_or(rest.len()))
+        } else if c.is_ascii_digit() {
+            (Number, number(rest))
+        } else {
+            (Punct(c), c.len_utf8())
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let rest = &self.src[self.pos..];
+        if rest.is_empty() { return None }
+
+        let (kind, len) = self.lex(rest);
+        let span = self.pos..(self.pos + len);
+
+        self.line_start = match kind {
+            TokenKind::Whitespace => self.line_start || newline(&rest[..len]),
+            TokenKind::Comment => self.line_start,
+            _ => false,
+        };
+        self.pos = span.end;
+
+        Some(Token { kind, span })
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || !c.is_ascii()
+}
+
+/// Length of a backslash-newline line splice, or 0.
+fn splice(s: &str) -> usize {
+    if s.starts_with("\\\n") { 2 } else if s.starts_with("\\\r\n") { 3 } else { 0 }
+}
+
+/// Contains a newline that isn't part of a line splice.
+fn newline(s: &str) -> bool {
+    s.match_indices('\n').any(|(i, _)| !s[..i].ends_with('\\') && !s[..i].ends_with("\\\r"))
+}
+
+/// Horizontal whitespace and line splices.
+fn space(s: &str) -> usize {
+    let mut i = 0;
+
+    loop {
+        let rest = &s[i..];
+        match rest.chars().next() {
+            Some(c) if c.is_whitespace() && c != '\n' => i += c.len_utf8(),
+            Some('\\') if splice(rest) > 0 => i += splice(rest),
+            _ => return i,
+        }
+    }
+}
+
+/// `#define NAME` or `#define NAME(params)`, with `s` starting at the `#`.
+fn define(s: &str) -> Option<usize> {
+    let mut i = 1 + space(&s[1..]);
+    i += s[i..].strip_prefix("define").map(|_| 6)?;
+
+    let n = space(&s[i..]);
+    let name = s[(i + n)..].find(|c| !is_ident_continue(c)).unwrap_or(s.len() - i - n);
+    if n == 0 || name == 0 { return None }
+    i += n + name;
+
+    // Function-like macros have no space before the parameters
+    if s[i..].starts_with('(') {
+        i += s[i..line(s)].find(')')? + 1;
+    }
+
+    Some(i)
+}
+
+fn whitespace(s: &str) -> usize {
+    let mut i = 0;
+
+    loop {
+        let rest = &s[i..];
+        match rest.chars().next() {
+            Some(c) if c.is_whitespace() => i += c.len_utf8(),
+            Some('\\') if splice(rest) > 0 => i += splice(rest),
+            _ => return i,
+        }
+    }
+}
+
+/// Up to (not including) the end of the line. Honours line splices.
+pub(crate) fn line(s: &str) -> usize {
+    let mut i = 0;
+
+    loop {
+        let rest = &s[i..];
+        match rest.chars().next() {
+            None | Some('\n') => return i,
+            Some('\\') if splice(rest) > 0 => i += splice(rest),
+            Some(c) => i += c.len_utf8(),
+        }
+    }
+}
+
+fn number(s: &str) -> usize {
+    let mut prev = '\0';
+
+    for (i, c) in s.char_indices() {
+        let ok = c.is_ascii_alphanumeric() || c == '.' || c == '_'
+            || (c == '\'' && s[i + 1..].starts_with(|c: char| c.is_ascii_alphanumeric()))
+            || ((c == '+' || c == '-') && matches!(prev, 'e' | 'E' | 'p' | 'P'));
+
+        if !ok { return i }
+        prev = c;
+    }
+
+    s.len()
+}
+
+/// String or character literal, with `s` starting at the opening quote.
+/// Unterminated literals stop at the end of the line.
+fn quoted(s: &str, q: char) -> usize {
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\n' => return i,
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == q => return i + 1,
+            _ => {},
+        }
+    }
+
+    s.len()
+}
+
+/// Prefixed literals: `u8"..."`, `L'x'`, `R"d(...)d"` etc.
+fn literal(prefix: &str, rest: &str) -> Option<(TokenKind, usize)> {
+    let p = prefix.len();
+
+    match prefix {
+        "R" | "LR" | "uR" | "UR" | "u8R" if rest.starts_with('"') => {
+            Some((TokenKind::Str, p + raw(rest).unwrap_or_else(|| quoted(rest, '"'))))
+        },
+        "L" | "u" | "U" | "u8" if rest.starts_with('"') => Some((TokenKind::Str, p + quoted(rest, '"'))),
+        "L" | "u" | "U" | "u8" if rest.starts_with('\'') => Some((TokenKind::Char, p + quoted(rest, '\''))),
+        _ => None,
+    }
+}
+
+/// Raw string body, with `s` starting at the opening quote.
+fn raw(s: &str) -> Option<usize> {
+    let open = s.find('(')?;
+    let delim = &s[1..open];
+
+    if delim.len() > 16 || delim.contains(|c: char| c.is_whitespace() || c == ')' || c == '\\') {
+        return None
+    }
+
+    let close = format!("){}\"", delim);
+    Some(s[open..].find(&close).map_or(s.len(), |i| open + i + close.len()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    fn kinds(src: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(src, Scope::Code).iter().map(|t| (t.kind, t.text(src))).collect()
+    }
+
+    #[test]
+    fn test_macro() {
+        assert_eq!(kinds("MOCK_METHOD0(Foo, int());"), vec![
+            (Ident, "MOCK_METHOD0"), (Punct('('), "("), (Ident, "Foo"), (Punct(','), ","),
+            (Whitespace, " "), (Ident, "int"), (Punct('('), "("), (Punct(')'), ")"),
+            (Punct(')'), ")"), (Punct(';'), ";"),
+        ]);
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_eq!(kinds("a // b\n/* c\n */d"), vec![
+            (Ident, "a"), (Whitespace, " "), (Comment, "// b"), (Whitespace, "\n"),
+            (Comment, "/* c\n */"), (Ident, "d"),
+        ]);
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(kinds(r#""a\")" u8"b" L'c' '\''"#), vec![
+            (Str, r#""a\")""#), (Whitespace, " "), (Str, r#"u8"b""#), (Whitespace, " "),
+            (Char, "L'c'"), (Whitespace, " "), (Char, r"'\''"),
+        ]);
+    }
+
+    #[test]
+    fn test_raw_string() {
+        assert_eq!(kinds(r#"R"x()")x" y"#), vec![
+            (Str, r#"R"x()")x""#), (Whitespace, " "), (Ident, "y"),
+        ]);
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(kinds("1'000 1e+5 .5f"), vec![
+            (Number, "1'000"), (Whitespace, " "), (Number, "1e+5"), (Whitespace, " "), (Number, ".5f"),
+        ]);
+    }
+
+    #[test]
+    fn test_directive() {
+        assert_eq!(kinds("#if A \\\n  B\nx # y"), vec![
+            (Directive, "#if A \\\n  B"), (Whitespace, "\n"), (Ident, "x"),
+            (Whitespace, " "), (Punct('#'), "#"), (Whitespace, " "), (Ident, "y"),
+        ]);
+    }
+
+    #[test]
+    fn test_define_body_is_code() {
+        assert_eq!(kinds("#define A \\\n  B\nx"), vec![
+            (Directive, "#define A"), (Whitespace, " \\\n  "), (Ident, "B"), (Whitespace, "\n"), (Ident, "x"),
+        ]);
+        assert_eq!(kinds("# define S(x, y) \\\n #x"), vec![
+            (Directive, "# define S(x, y)"), (Whitespace, " \\\n "), (Punct('#'), "#"), (Ident, "x"),
+        ]);
+        assert_eq!(kinds("#define X (1)"), vec![
+            (Directive, "#define X"), (Whitespace, " "), (Punct('('), "("), (Number, "1"), (Punct(')'), ")"),
+        ]);
+        assert_eq!(kinds("#defined"), vec![(Directive, "#defined")]);
+    }
+
+    #[test]
+    fn test_indented_directive() {
+        assert_eq!(kinds("  #if X\n"), vec![
+            (Whitespace, "  "), (Directive, "#if X"), (Whitespace, "\n"),
+        ]);
+    }
+
+    #[test]
+    fn test_unterminated() {
+        assert_eq!(kinds("\"abc\nd /* e"), vec![
+            (Str, "\"abc"), (Whitespace, "\n"), (Ident, "d"), (Whitespace, " "), (Comment, "/* e"),
+        ]);
+    }
+
+    #[test]
+    fn test_scope_all() {
+        let src = "// 'a' \"b\"";
+        let all: Vec<_> = tokenize(src, Scope::All).iter().map(|t| (t.kind, t.text(src))).collect();
+
+        assert_eq!(all, vec![
+            (Punct('/'), "/"), (Punct('/'), "/"), (Whitespace, " "), (Punct('\''), "'"), (Ident, "a"),
+            (Punct('\''), "'"), (Whitespace, " "), (Punct('"'), "\""), (Ident, "b"), (Punct('"'), "\""),
+        ]);
+    }
+}
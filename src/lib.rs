@@ -1,11 +1,35 @@
+mod alias;
+mod check;
+mod conditions;
+mod defines;
 mod errors;
 mod extract;
+mod lexer;
+mod parse;
 mod regexes;
 mod search;
+mod split;
 mod replace;
 
+pub use alias::Alias;
+pub use conditions::Condition;
+pub use errors::GmockSedError;
+
+pub use lexer::Scope;
+
+pub use parse::parse_mock_macros;
+pub use parse::{OldMockMacro, Variant};
+
 pub use search::search;
-pub use search::{SearchMode, SearchSummary};
+pub use search::{SearchMatch, SearchMode, SearchOptions, SearchSummary};
 
 pub use replace::replace;
-pub use replace::ReplaceSummary;
+pub use replace::{Arity, Conversion, Edit, ReplaceError, ReplaceOptions, ReplaceSummary};
+
+pub use check::check;
+pub use check::{CheckSummary, Offender};
+
+/// Compiles the examples in README.md, so they keep up with the API.
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub struct ReadmeDoctests;
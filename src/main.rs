@@ -1,75 +1,296 @@
-/// gmock-sed: Simple CLI tool for updating gMock macros. (MOCK_METHODn -> MOCK_METHOD)
+//! gmock-sed: Simple CLI tool for updating gMock macros. (MOCK_METHODn -> MOCK_METHOD)
 
 mod app;
+mod baseline;
+mod config;
+mod diagnostic;
+mod diff;
+mod fixes;
+mod json;
+mod outcome;
+mod output;
+mod sarif;
 mod util;
 
 use std::path::PathBuf;
+use std::io::Write;
+use std::process::ExitCode;
+use std::sync::mpsc;
+use colored::*;
 use rayon::prelude::*;
-use walkdir::WalkDir;
 
-use gmock_sed::{ReplaceSummary, SearchSummary, SearchMode};
+use gmock_sed::{CheckSummary, GmockSedError, ReplaceOptions, ReplaceSummary, SearchOptions, SearchSummary, SearchMode, Scope};
+use app::Format;
+use outcome::FileOutcome;
+use output::Found;
 
-fn main() {
+fn main() -> ExitCode {
     use structopt::StructOpt;
-    use app::Opt::*;
-    match app::Opt::from_args() {
-        Search { count, max_depth, dir } => {
-            let walker = WalkDir::new(dir).max_depth(max_depth);
-
-            let files: Vec<PathBuf> =
-                walker.into_iter()
-                      .filter_map(Result::ok)
-                      .filter(util::is_file)
-                      .filter(util::is_cpp)
-                      .map(|de| de.into_path())
-                      .collect();
+    use app::Command::*;
+    let opt = app::Opt::from_args();
+
+    let config = match config::Config::load(opt.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => return report(&[e]),
+    };
+    let aliases = config.aliases.as_slice();
+
+    match opt.cmd {
+        Search { count, line_numbers, context, format, include_comments, mut walk, dir } => {
+            walk.configure(&config);
+
+            let positions = line_numbers || context.is_some() || format != Format::Text;
+            let mode = if positions { SearchMode::Positions } else { SearchMode::from(count) };
+            let options = SearchOptions { scope: Scope::from(include_comments), aliases };
+
+            let mut failed = Vec::new();
+            let (tx, rx) = mpsc::channel();
+            let (dir, walk) = (&dir, &walk);
 
-            let mode = SearchMode::from(count);
+            std::thread::scope(|s| {
+                // Search files as they're found. Print results as they arrive.
+                s.spawn(move || util::walk_cpp_files(dir, walk, |file| {
+                    let result = file.and_then(|f| util::read(&f).map(|cpp| {
+                        match (gmock_sed::search(&cpp, mode, &options), format) {
+                            (r, _) if !r.is_match => Found::Records(Vec::new()),
+                            (r, Format::Json | Format::JsonLines) => Found::Records(vec![json::search(&f, &r)]),
+                            (_, Format::Sarif) => {
+                                let summary = gmock_sed::replace(&cpp, &ReplaceOptions { scope: options.scope, aliases, ..Default::default() });
+                                Found::Records(sarif::results(&f, &cpp, &summary))
+                            },
+                            (r, Format::Text) if positions => Found::Lines(output::matches(&f, &cpp, &r, context)),
+                            (r, Format::Text) => Found::Lines(format!("{}{}\n", &f.to_string_lossy(), r)),
+                        }
+                    }));
+                    tx.send(result).ok();
+                }));
 
-            let results: Vec<SearchSummary> =
+                fast_stdout!(stdout);
+                let mut records = Vec::new();
+
+                #[allow(unused)]
+                for r in rx {
+                    match r {
+                        Ok(Found::Lines(lines)) => { write!(stdout, "{}", lines); },
+                        Ok(Found::Records(r)) if format == Format::JsonLines => for v in r { writeln!(stdout, "{}", v); },
+                        Ok(Found::Records(r)) => records.extend(r),
+                        Err(e) => failed.push(e),
+                    }
+                }
+
+                #[allow(unused)]
+                match format {
+                    Format::Json => { writeln!(stdout, "{}", serde_json::Value::from(records)); },
+                    Format::Sarif => { writeln!(stdout, "{:#}", sarif::log(records)); },
+                    Format::Text | Format::JsonLines => {},
+                }
+            });
+
+            report(&failed)
+        },
+
+        Check { include_comments, format, arity, mut walk, baseline: baseline_path, dir } => {
+            walk.configure(&config);
+
+            let allowed = match baseline_path.as_deref().map(baseline::Baseline::load).transpose() {
+                Ok(allowed) => allowed,
+                Err(e) => return report(&[e]),
+            };
+
+            let mut failed = Vec::new();
+            let files = util::cpp_files(&dir, &walk, &mut failed);
+            let options = ReplaceOptions { scope: Scope::from(include_comments), arity, aliases, ..Default::default() };
+
+            let results: Vec<Result<(CheckSummary, Vec<serde_json::Value>), GmockSedError>> =
                 files.par_iter()
-                     .map(|pb| util::read(&pb))
-                     .map(|cpp| gmock_sed::search(&cpp, mode))
+                     .map(|pb| util::read(pb).map(|cpp| {
+                         // One pass serves both, so SARIF agrees with the exit code
+                         let summary = gmock_sed::replace(&cpp, &options);
+                         let findings = match format {
+                             Format::Sarif => sarif::results(pb, &cpp, &summary),
+                             _ => Vec::new(),
+                         };
+                         (CheckSummary::from(&summary), findings)
+                     }))
                      .collect();
 
+            let mut current = baseline::Baseline::default();
+            let (mut remaining, mut unconvertible) = (0, 0);
+            let mut findings = Vec::new();
+
             fast_stdout!(stdout);
 
             #[allow(unused)]
-            for (f, r) in files.iter().zip(results.iter()) {
-                if r.is_match {
-                    writeln!(stdout, "{}{}", &f.to_string_lossy(), r);
+            for (f, r) in files.iter().zip(results) {
+                match r {
+                    Ok((r, sarif)) => {
+                        let key = util::relative(f, &dir);
+                        current.insert(&key, r.offenders.len());
+
+                        if allowed.as_ref().is_some_and(|b| r.offenders.len() <= b.allowed(&key)) {
+                            continue
+                        }
+
+                        remaining += r.offenders.len();
+                        unconvertible += r.failures();
+                        findings.extend(sarif);
+
+                        if format == Format::Text {
+                            for o in r.offenders.iter() {
+                                writeln!(stdout, "{}{}", &f.to_string_lossy(), o);
+                            }
+                        }
+                    },
+                    Err(e) => failed.push(e),
+                }
+            }
+
+            #[allow(unused)]
+            if format == Format::Sarif {
+                writeln!(stdout, "{:#}", sarif::log(findings));
+            }
+
+            drop(stdout);
+
+            if let (Some(path), Some(allowed)) = (baseline_path, allowed) {
+                if remaining == 0 && failed.is_empty() && allowed.improved_by(&current) {
+                    match current.save(&path) {
+                        Ok(()) => eprintln!("{}: baseline lowered", path.display()),
+                        Err(e) => failed.push(e),
+                    }
                 }
             }
+
+            report(&failed);
+
+            match (unconvertible + failed.len(), remaining) {
+                (0, 0) => ExitCode::SUCCESS,
+                (0, _) => ExitCode::from(1),
+                (_, _) => ExitCode::from(2),
+            }
         },
 
-        Replace { dry_run, show_errors, add_override, files } => {
-            let results: Vec<ReplaceSummary> =
+        Baseline { include_comments, mut walk, output, dir } => {
+            walk.configure(&config);
+
+            let mut failed = Vec::new();
+            let files = util::cpp_files(&dir, &walk, &mut failed);
+            let options = SearchOptions { scope: Scope::from(include_comments), aliases };
+
+            let results: Vec<Result<SearchSummary, GmockSedError>> =
                 files.par_iter()
-                     .map(|p| util::read(&p))
-                     .filter(|s| !s.is_empty())
-                     .map(|cpp| gmock_sed::replace(&cpp, add_override))
+                     .map(|pb| util::read(pb).map(|cpp| gmock_sed::search(&cpp, SearchMode::Full, &options)))
                      .collect();
 
-            let mut errors = Vec::new();
+            let mut counts = baseline::Baseline::default();
 
-            for (file, result) in files.iter().zip(results.iter()) {
-                println!("{}: {}", file.display(), result);
+            for (f, r) in files.iter().zip(results) {
+                match r {
+                    Ok(r) => counts.insert(&util::relative(f, &dir), r.count.unwrap_or(0)),
+                    Err(e) => failed.push(e),
+                }
+            }
 
-                match (result.error_free(), dry_run) {
-                    (true, true)  => {},
-                    (true, false) => util::write(file, result),
-                    (false, _)    => errors.push((file, result)),
+            if let Err(e) = counts.save(&output) {
+                failed.push(e);
+            }
+
+            report(&failed)
+        },
+
+        Replace { dry_run, diff, color, export_fixes, format, show_errors, arity, add_override, include_comments, all_files, mut walk, files } => {
+            walk.configure(&config);
+
+            let options = ReplaceOptions { add_override, scope: Scope::from(include_comments), arity, aliases };
+            color.apply();
+
+            let mut failed = Vec::new();
+
+            // (path, found by walking a directory)
+            let files: Vec<(PathBuf, bool)> =
+                files.into_iter()
+                     .flat_map(|p| match p.is_dir() {
+                         true => util::cpp_files(&p, &walk, &mut failed).into_iter().map(|f| (f, true)).collect(),
+                         false => vec![(p, false)],
+                     })
+                     .collect();
+
+            let mut outcomes: Vec<FileOutcome> =
+                files.par_iter()
+                     .map(|(p, walked)| (FileOutcome::replace(p, &options), walked))
+                     .filter(|(o, walked)| !**walked || all_files || o.is_flagged())
+                     .map(|(o, _)| o)
+                     .collect();
+
+            let mut records = Vec::new();
+
+            for outcome in outcomes.iter_mut() {
+                outcome.write(dry_run || diff || export_fixes.is_some());
+
+                match (outcome.io_error(), diff, format) {
+                    (_, _, Format::JsonLines) => println!("{}", json::replace(outcome)),
+                    (_, _, Format::Json) => records.push(json::replace(outcome)),
+                    (_, _, Format::Sarif) => unreachable!("rejected by the argument parser"),
+                    (Some(_), _, _) => {},
+                    (None, true, _) => print!("{}", outcome.diff()),
+                    (None, false, _) => println!("{}", outcome),
+                }
+
+                if format == Format::Text {
+                    eprint!("{}", outcome.warnings());
                 }
             }
 
-            if show_errors && !errors.is_empty() {
-                println!("\nErrors Summary ({})", errors.len());
+            if format == Format::Json {
+                println!("{}", serde_json::Value::from(records));
+            }
+
+            if let Some(path) = export_fixes.as_deref() {
+                if let Err(e) = fixes::export(path, &outcomes) {
+                    failed.push(e);
+                }
+            }
+
+            let errors: Vec<&FileOutcome> = outcomes.iter().filter(|o| o.failed().is_some()).collect();
+
+            // Structured output already carries the errors
+            if show_errors && format == Format::Text && !errors.is_empty() {
+                // Keep stdout a clean patch
+                let mut out: Box<dyn Write> = match diff {
+                    true => Box::new(std::io::stderr()),
+                    false => Box::new(std::io::stdout()),
+                };
+
+                let count: usize = errors.iter().filter_map(|o| o.failed()).map(ReplaceSummary::error_count).sum();
+
+                #[allow(unused)]
+                {
+                    writeln!(out);
 
-                for (file, result) in errors.iter() {
-                    println!(" {}:\n{}", file.display(), result.error_summary());
+                    for outcome in errors.iter() {
+                        write!(out, "{}", outcome.diagnostics());
+                    }
+
+                    writeln!(out, "{} macro(s) in {} file(s) could not be converted", count, errors.len());
                 }
             }
+
+            let failed: Vec<&GmockSedError> =
+                failed.iter()
+                      .chain(outcomes.iter().filter_map(FileOutcome::io_error))
+                      .collect();
+
+            report(&failed)
         }
     }
 }
 
+/// Print I/O errors to stderr. Any error makes for a non-zero exit code.
+fn report<E: std::fmt::Display>(errors: &[E]) -> ExitCode {
+    for e in errors {
+        eprintln!("{}: {}", "error".red(), e);
+    }
+
+    if errors.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
@@ -0,0 +1,125 @@
+//! Per-file results of `replace`. Keeps each outcome attached to its path.
+
+use std::fmt;
+use std::path::Path;
+use colored::*;
+
+use gmock_sed::{GmockSedError, ReplaceOptions, ReplaceSummary};
+use crate::{diagnostic, diff, util};
+use crate::diagnostic::Level;
+
+pub struct FileOutcome<'p> {
+    pub path: &'p Path,
+    source: String,
+    pub read: ReadStatus,
+    pub summary: Option<ReplaceSummary>,
+    pub write: WriteStatus,
+}
+
+pub enum ReadStatus {
+    Read,
+    Empty,
+    Failed(GmockSedError),
+}
+
+pub enum WriteStatus {
+    Pending,
+    Written,
+    DryRun,
+    /// Nothing to change, or errors prevented a partial rewrite.
+    Unchanged,
+    /// Nothing was read.
+    Skipped,
+    Failed(GmockSedError),
+}
+
+impl<'p> FileOutcome<'p> {
+    pub fn replace(path: &'p Path, options: &ReplaceOptions) -> Self {
+        let (source, read, summary) = match util::read(path) {
+            Ok(cpp) if cpp.is_empty() => (cpp, ReadStatus::Empty, None),
+            Ok(cpp) => {
+                let summary = gmock_sed::replace(&cpp, options);
+                (cpp, ReadStatus::Read, Some(summary))
+            },
+            Err(e) => (String::new(), ReadStatus::Failed(e), None),
+        };
+
+        FileOutcome { path, source, read, summary, write: WriteStatus::Pending }
+    }
+
+    pub fn write(&mut self, dry_run: bool) {
+        self.write = match (self.summary.as_ref(), dry_run) {
+            (None, _) => WriteStatus::Skipped,
+            (Some(r), _) if !r.error_free() => WriteStatus::Unchanged,
+            (Some(_), true) => WriteStatus::DryRun,
+            (Some(r), false) => match util::write(self.path, r) {
+                Ok(()) => WriteStatus::Written,
+                Err(e) => WriteStatus::Failed(e),
+            },
+        }
+    }
+
+    pub fn io_error(&self) -> Option<&GmockSedError> {
+        match (&self.read, &self.write) {
+            (ReadStatus::Failed(e), _) | (_, WriteStatus::Failed(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Contains at least one old-style macro, or couldn't be read to find out.
+    pub fn is_flagged(&self) -> bool {
+        match (&self.read, self.summary.as_ref()) {
+            (ReadStatus::Failed(_), _) => true,
+            (_, Some(r)) => r.is_match(),
+            (_, None) => false,
+        }
+    }
+
+    /// Unified diff of what `write` would change. Empty when nothing would be written.
+    pub fn diff(&self) -> String {
+        match self.summary.as_ref() {
+            Some(r) if r.error_free() => {
+                let new = r.suggestion.as_deref().unwrap_or_default();
+                diff::unified(self.path, &self.source, new)
+            },
+            _ => String::new(),
+        }
+    }
+
+    /// A diagnostic for each macro that couldn't be converted.
+    pub fn diagnostics(&self) -> String {
+        let errors = self.summary.as_ref().map_or(&[][..], |r| r.errors());
+
+        errors.iter().map(|e| diagnostic::render(self.path, &self.source, e, Level::Error) + "\n").collect()
+    }
+
+    /// A diagnostic for each macro that was converted despite a problem.
+    pub fn warnings(&self) -> String {
+        let warnings = self.summary.as_ref().map_or(&[][..], |r| r.warnings());
+
+        warnings.iter().map(|w| diagnostic::render(self.path, &self.source, w, Level::Warning) + "\n").collect()
+    }
+
+    /// Summary of a file with at least one failed conversion.
+    pub fn failed(&self) -> Option<&ReplaceSummary> {
+        self.summary.as_ref().filter(|r| r.error_count() > 0)
+    }
+}
+
+impl<'p> fmt::Display for FileOutcome<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path.display())?;
+
+        match (&self.read, self.summary.as_ref()) {
+            (ReadStatus::Empty, _) => write!(f, "{}", "skipped (empty file)".yellow())?,
+            (ReadStatus::Failed(_), _) => write!(f, "{}", "unreadable".red())?,
+            (ReadStatus::Read, Some(r)) => write!(f, "{}", r)?,
+            (ReadStatus::Read, None) => {},
+        }
+
+        match (&self.write, self.failed()) {
+            (WriteStatus::Unchanged, Some(_)) => write!(f, " {}", "not written".red()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Formatting of search results.
+
+use std::fmt::Write;
+use std::path::Path;
+use serde_json::Value;
+
+use gmock_sed::SearchSummary;
+
+/// What was found in one file, ready to print.
+pub enum Found {
+    Lines(String),
+    /// Printed as they come with `jsonl`, otherwise once all files are done.
+    Records(Vec<Value>),
+}
+
+/// One `path:line:column: MACRO_NAME` line per match.
+///
+/// With `context`, the source is printed instead, grep style: matching lines as
+/// `path:line:column:text` (first match on the line), the lines around them as
+/// `path-line-text`. Groups that don't touch are separated by `--`.
+pub fn matches(path: &Path, source: &str, summary: &SearchSummary, context: Option<usize>) -> String {
+    let path = path.to_string_lossy();
+    let mut out = String::new();
+
+    let context = match context {
+        Some(c) => c,
+        None => {
+            for m in summary.matches.iter() {
+                writeln!(out, "{}{}", path, m).ok();
+            }
+            return out
+        },
+    };
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut matches = summary.matches.iter().peekable();
+    // Last line printed, 1-based
+    let mut printed = 0;
+
+    while let Some(first) = matches.peek() {
+        let from = first.line.saturating_sub(context).max(printed + 1);
+        if printed > 0 && from > printed + 1 {
+            out.push_str("--\n");
+        }
+
+        let mut n = from;
+        let mut to = first.line + context;
+
+        // Overlapping windows are merged
+        while n <= to.min(lines.len()) {
+            match matches.next_if(|m| m.line == n) {
+                Some(m) => {
+                    while matches.next_if(|m| m.line == n).is_some() {}
+                    writeln!(out, "{}:{}:{}:{}", path, n, m.column, lines[n - 1]).ok();
+                    to = to.max(n + context);
+                },
+                None => { writeln!(out, "{}-{}-{}", path, n, lines[n - 1]).ok(); },
+            }
+
+            printed = n;
+            n += 1;
+        }
+    }
+
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gmock_sed::{search, SearchMode, SearchOptions};
+
+    const CPP: &str = "class A {\n  MOCK_METHOD0(A, int());\n  MOCK_METHOD0(B, int());\n\n\n\n  MOCK_METHOD0(C, int());\n};";
+
+    fn format(context: Option<usize>) -> String {
+        matches(Path::new("a.h"), CPP, &search(CPP, SearchMode::Positions, &SearchOptions::default()), context)
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        assert_eq!(format(None), "a.h:2:3: MOCK_METHOD0\na.h:3:3: MOCK_METHOD0\na.h:7:3: MOCK_METHOD0\n");
+    }
+
+    #[test]
+    fn test_context() {
+        let expected = "\
+a.h-1-class A {
+a.h:2:3:  MOCK_METHOD0(A, int());
+a.h:3:3:  MOCK_METHOD0(B, int());
+a.h-4-
+--
+a.h-6-
+a.h:7:3:  MOCK_METHOD0(C, int());
+a.h-8-};
+";
+        assert_eq!(format(Some(1)), expected);
+    }
+
+    #[test]
+    fn test_zero_context() {
+        let expected = "\
+a.h:2:3:  MOCK_METHOD0(A, int());
+a.h:3:3:  MOCK_METHOD0(B, int());
+--
+a.h:7:3:  MOCK_METHOD0(C, int());
+";
+        assert_eq!(format(Some(0)), expected);
+    }
+
+    #[test]
+    fn test_two_matches_on_a_line() {
+        let cpp = "MOCK_METHOD0(A, int()); MOCK_METHOD0(B, int());";
+        let found = matches(Path::new("a.h"), cpp, &search(cpp, SearchMode::Positions, &SearchOptions::default()), Some(0));
+
+        assert_eq!(found, format!("a.h:1:1:{}\n", cpp));
+    }
+}
@@ -0,0 +1,622 @@
+//! Parse old-style MOCK_METHODn macros into typed structs.
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::alias::{recognise, Alias};
+use crate::errors::GmockSedError;
+use crate::extract::{lextract, rextract};
+use crate::lexer::{tokenize, Scope, Token, TokenKind};
+use crate::regexes::MACRO_REGEX;
+use crate::split;
+
+/// Every old-style macro in `src` that could be parsed. Comments and literals are skipped.
+pub fn parse_mock_macros(src: &str) -> Vec<OldMockMacro> {
+    parse(src, Scope::Code, &[]).into_iter().filter_map(Result::ok).collect()
+}
+
+/// Errors come with the span of the whole macro, as far as it could be delimited.
+pub(crate) type Spanned<T> = Result<T, (Range<usize>, GmockSedError)>;
+
+pub(crate) fn parse(src: &str, scope: Scope, aliases: &[Alias]) -> Vec<Spanned<OldMockMacro>> {
+    let tokens = tokenize(src, scope);
+
+    invocations(src, &tokens, aliases)
+        .into_iter()
+        .map(|inv| inv.and_then(|inv| OldMockMacro::new(src, &inv)))
+        .collect()
+}
+
+/// Old-style macro, e.g. `MOCK_CONST_METHOD1(Foo, bool(int));`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OldMockMacro {
+    /// Byte range in the source, including the trailing semicolon if any.
+    pub span: Range<usize>,
+    /// 1-based line of the macro name.
+    pub line: usize,
+    /// 1-based column of the macro name, counted in characters.
+    pub column: usize,
+    /// Macro name as written, e.g. `MOCK_CONST_METHOD1`.
+    pub macro_name: String,
+    pub variant: Variant,
+    /// The n in MOCK_METHODn.
+    pub arity: usize,
+    pub name: String,
+    pub return_type: String,
+    /// Text between the argument parentheses. Whitespace is kept as is.
+    pub args: String,
+    /// Byte range of `args` in the source.
+    pub args_span: Range<usize>,
+    /// Each parameter declared, split at top-level commas and trimmed, e.g.
+    /// `["int", "std::map<int, int> m"]`. Empty for `()` and `(void)`.
+    pub params: Vec<String>,
+    pub calltype: Option<String>,
+    pub semicolon: bool,
+    /// New-style macro it converts to: `MOCK_METHOD` unless aliased.
+    pub target: String,
+    /// Parts as written, comments included, for the new macro.
+    pub(crate) written: Written,
+}
+
+/// Parts of a macro with the comments around them. Only whitespace is trimmed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Written {
+    /// Between the macro name and its `(`, if there are comments.
+    pub(crate) gap: String,
+    pub(crate) name: String,
+    pub(crate) return_type: String,
+    pub(crate) calltype: Option<String>,
+}
+
+impl OldMockMacro {
+    fn new(src: &str, inv: &Invocation) -> Spanned<Self> {
+        let error = |e| (inv.span.clone(), e);
+
+        let macro_name = inv.name.text(src);
+        let (variant, arity) = (inv.variant, inv.arity);
+        let mut params = inv.params;
+
+        let calltype = if variant.with_calltype {
+            let (ct, rest) = split_first(params).ok_or_else(|| {
+                let end = bytes(trim(params), inv.open.span.end).end;
+                error(GmockSedError::MissingMethodName { span: end..end })
+            })?;
+            params = rest;
+            Some(ct)
+        } else {
+            None
+        };
+
+        let sig = signature(src, params, inv.open.span.end).map_err(error)?;
+        let (line, column) = position(src, inv.span.start);
+        let args = &src[sig.args.clone()];
+
+        let gap = &src[inv.name.span.end..inv.open.span.start];
+        let commented = tokenize(gap, Scope::Code).iter().any(|t| t.kind == TokenKind::Comment);
+
+        let written = Written {
+            gap: if commented { gap.to_owned() } else { String::new() },
+            name: text(src, sig.name).to_owned(),
+            // `after` is just comments unless the return type is a declarator
+            return_type: match trim(sig.after).is_empty() && !sig.after.is_empty() {
+                true => format!("{} {}", text(src, sig.before), text(src, sig.after)),
+                false => format!("{}{}", text(src, sig.before), text(src, sig.after)),
+            },
+            calltype: calltype.map(|ct| text(src, trim_space(ct)).to_owned()),
+        };
+
+        Ok(OldMockMacro {
+            span: inv.span.clone(),
+            line,
+            column,
+            macro_name: macro_name.to_owned(),
+            variant,
+            arity,
+            name: text(src, trim(sig.name)).to_owned(),
+            return_type: format!("{}{}", text(src, trim(sig.before)), text(src, trim(sig.after))),
+            args: args.to_owned(),
+            params: split::parameters(args).into_iter().map(|r| args[r].to_owned()).collect(),
+            args_span: sig.args,
+            calltype: calltype.map(|ct| text(src, trim(ct)).to_owned()),
+            semicolon: inv.semicolon,
+            target: inv.target.to_owned(),
+            written,
+        })
+    }
+
+    /// Error unless the n in MOCK_METHODn is the number of parameters declared.
+    pub fn check_arity(&self) -> Result<(), GmockSedError> {
+        let found = self.params.len();
+
+        match found == self.arity {
+            true => Ok(()),
+            false => Err(GmockSedError::ArityMismatch { span: self.args_span.clone(), expected: self.arity, found }),
+        }
+    }
+}
+
+/// Which flavour of old-style macro was used.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Variant {
+    /// MOCK_CONST_METHODn
+    pub is_const: bool,
+    /// MOCK_METHODn_T
+    pub is_template: bool,
+    /// MOCK_METHODn_WITH_CALLTYPE
+    pub with_calltype: bool,
+}
+
+impl Variant {
+    /// Variant and n of a canonical gMock macro name.
+    pub(crate) fn parse(_macro: &str) -> Option<(Self, usize)> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(MACRO_REGEX).unwrap();
+        }
+
+        let c = RE.captures(_macro)?;
+        let variant = Variant {
+            is_const: c.get(1).is_some(),
+            is_template: c.get(3).is_some(),
+            with_calltype: c.get(4).is_some(),
+        };
+
+        Some((variant, c[2].parse::<usize>().unwrap()))
+    }
+}
+
+/// Parses the `Display` form, e.g. `MOCK_CONST_METHODn_T`.
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let variant = s.contains("METHODn").then(|| Variant::parse(&s.replacen("METHODn", "METHOD0", 1))).flatten();
+
+        variant.map(|(v, _)| v).ok_or_else(|| format!("invalid variant '{}' (e.g. MOCK_METHODn, MOCK_CONST_METHODn_T)", s))
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = if self.is_const { "CONST_" } else { "" };
+        let t = if self.is_template { "_T" } else { "" };
+        let ct = if self.with_calltype { "_WITH_CALLTYPE" } else { "" };
+
+        write!(f, "MOCK_{}METHODn{}{}", c, t, ct)
+    }
+}
+
+/// Old-style macro call, delimited by balanced parentheses.
+struct Invocation<'t, 'a> {
+    name: &'t Token,
+    open: &'t Token,
+    params: &'t [Token],
+    span: Range<usize>,
+    semicolon: bool,
+    variant: Variant,
+    arity: usize,
+    target: &'a str,
+}
+
+fn invocations<'t, 'a>(src: &str, tokens: &'t [Token], aliases: &'a [Alias]) -> Vec<Spanned<Invocation<'t, 'a>>> {
+    let mut found = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let name = &tokens[i];
+        i += 1;
+
+        if name.kind != TokenKind::Ident { continue }
+        let Some((variant, arity, target)) = recognise(name.text(src), aliases) else { continue };
+
+        let open = skip(tokens, i, Token::is_trivia);
+        if !tokens.get(open).is_some_and(|t| t.is_punct('(')) {
+            let construct = "macro name without an argument list";
+            found.push(Err((name.span.clone(), GmockSedError::UnsupportedConstruct { span: name.span.clone(), construct })));
+            continue
+        }
+
+        let params = match lextract(&tokens[open..]) {
+            Ok(r) => (open + r.start)..(open + r.end),
+            Err(e) => {
+                let eol = src[name.span.start..].find('\n').map_or(src.len(), |i| name.span.start + i);
+                found.push(Err((name.span.start..eol, e)));
+                continue
+            },
+        };
+
+        i = params.end + 1;
+
+        let after = skip(tokens, i, |t| t.kind == TokenKind::Whitespace);
+        let semicolon = tokens.get(after).is_some_and(|t| t.is_punct(';'));
+        if semicolon { i = after + 1 }
+
+        found.push(Ok(Invocation {
+            name,
+            open: &tokens[open],
+            params: &tokens[params],
+            span: name.span.start..tokens[i - 1].span.end,
+            semicolon,
+            variant,
+            arity,
+            target,
+        }));
+    }
+
+    found
+}
+
+/// Parts of `Name, Return(Args)`, with the comments around them.
+struct Signature<'t> {
+    name: &'t [Token],
+    /// Return type before and after the parameter list. `after` is only
+    /// non-empty for declarators, e.g. `void(*` and `)(int)`.
+    before: &'t [Token],
+    after: &'t [Token],
+    /// Byte range between the parameter list's parentheses.
+    args: Range<usize>,
+}
+
+/// Split `Name, Return(Args)`. `at` is where the tokens start, for pointing at empty signatures.
+fn signature<'t>(src: &str, tokens: &'t [Token], at: usize) -> Result<Signature<'t>, GmockSedError> {
+    use GmockSedError::*;
+
+    let (name, rest) = match split_first(tokens) {
+        Some(split) => split,
+        // Either half could be missing. Guess from the shape of what's there.
+        None => {
+            let all = bytes(trim(tokens), at);
+            return Err(match trim(tokens).last() {
+                Some(t) if t.is_punct(')') => MissingMethodName { span: all.start..all.start },
+                _ if all.is_empty() => MissingMethodName { span: all },
+                _ => UnparseableReturnType { span: all.end..all.end, found: String::new() },
+            })
+        },
+    };
+
+    let comma = &tokens[name.len()];
+    let (name, rest) = (trim_space(name), trim_space(rest));
+    let code = trim(rest);
+
+    if trim(name).is_empty() {
+        return Err(MissingMethodName { span: comma.span.start..comma.span.start })
+    }
+
+    let unparseable = || UnparseableReturnType { span: bytes(code, comma.span.end), found: text(src, code).to_owned() };
+
+    if !code.last().is_some_and(|t| t.is_punct(')') || t.is_punct(']')) {
+        return Err(unparseable())
+    }
+
+    let params = parameters(code)?.ok_or_else(unparseable)?;
+    let offset = skip(rest, 0, Token::is_trivia);
+    let params = (offset + params.start)..(offset + params.end);
+    let (before, after) = (trim_space(&rest[..params.start]), trim_space(&rest[params.end..]));
+
+    if trim(before).is_empty() {
+        return Err(unparseable())
+    }
+
+    // Keep whitespace between the parentheses as is
+    let open = rest[params.start].span.end;
+    let close = rest[params.end - 1].span.start;
+
+    Ok(Signature { name, before, after, args: open..close })
+}
+
+/// Indices of the method's parameter list in a function type, parentheses
+/// included. That's the last group, e.g. `bool(int)`, unless the function
+/// returns a pointer or reference to a function or array. Then it's nested
+/// in the declarator, e.g. `void(*(int))(char)` takes an `int`.
+fn parameters(tokens: &[Token]) -> Result<Option<Range<usize>>, GmockSedError> {
+    let mut angles = 0usize;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match tokens[i].kind {
+            TokenKind::Punct('<') if !split::is_operator(tokens, i) => angles += 1,
+            TokenKind::Punct('>') if angles > 0 && !split::is_operator(tokens, i) => angles -= 1,
+            TokenKind::Punct('(') => {
+                let inner = lextract(&tokens[i..])?;
+                let inner = (i + inner.start)..(i + inner.end);
+
+                if angles == 0 && is_declarator(&tokens[inner.clone()]) {
+                    let declarator = trim(&tokens[inner.clone()]);
+                    let offset = inner.start + skip(&tokens[inner.clone()], 0, Token::is_trivia);
+
+                    return match parameters(declarator)? {
+                        Some(nested) => Ok(Some((offset + nested.start)..(offset + nested.end))),
+                        None => {
+                            let span = bytes(declarator, tokens[i].span.end);
+                            let construct = "function type without a parameter list, e.g. `void(*)(int)`; \
+                                             declare a `using` alias for it and mock a function returning the alias";
+                            Err(GmockSedError::UnsupportedConstruct { span, construct })
+                        },
+                    }
+                }
+
+                i = inner.end;
+            },
+            _ => {},
+        }
+
+        i += 1;
+    }
+
+    if !tokens.last().is_some_and(|t| t.is_punct(')')) {
+        return Ok(None)
+    }
+
+    let inner = rextract(tokens)?;
+    Ok(Some((inner.start - 1)..(inner.end + 1)))
+}
+
+/// Starts like a pointer, reference or pointer-to-member declarator, e.g. `*`,
+/// `&` or `Foo::*`, rather than a parameter.
+fn is_declarator(tokens: &[Token]) -> bool {
+    let tokens = trim(tokens);
+    let qualified = skip(tokens, 0, |t| t.kind == TokenKind::Ident || t.is_punct(':') || t.is_trivia());
+
+    match tokens.get(qualified) {
+        Some(t) if qualified == 0 => t.is_punct('*') || t.is_punct('&') || t.is_punct('^'),
+        Some(t) => t.is_punct('*') && tokens[qualified - 1].is_punct(':'),
+        None => false,
+    }
+}
+
+/// Split at the first comma outside of parentheses.
+fn split_first(tokens: &[Token]) -> Option<(&[Token], &[Token])> {
+    let mut n = 0;
+
+    for (i, t) in tokens.iter().enumerate() {
+        match t.kind {
+            TokenKind::Punct('(') => n += 1,
+            TokenKind::Punct(')') => n -= 1,
+            TokenKind::Punct(',') if n == 0 => return Some((&tokens[..i], &tokens[(i + 1)..])),
+            _ => {},
+        }
+    }
+
+    None
+}
+
+fn skip(tokens: &[Token], from: usize, f: impl Fn(&Token) -> bool) -> usize {
+    tokens[from..].iter().position(|t| !f(t)).map_or(tokens.len(), |i| from + i)
+}
+
+fn trim(tokens: &[Token]) -> &[Token] {
+    let l = skip(tokens, 0, Token::is_trivia);
+    let t = tokens.iter().rposition(|t| !t.is_trivia()).map_or(l, |i| i + 1);
+
+    &tokens[l..t]
+}
+
+/// Like `trim`, but keeps comments.
+fn trim_space(tokens: &[Token]) -> &[Token] {
+    let space = |t: &Token| t.kind == TokenKind::Whitespace;
+    let l = skip(tokens, 0, space);
+    let t = tokens.iter().rposition(|t| !space(t)).map_or(l, |i| i + 1);
+
+    &tokens[l..t]
+}
+
+/// Byte range covered by `tokens`, or an empty range at `at` if there are none.
+fn bytes(tokens: &[Token], at: usize) -> Range<usize> {
+    match (tokens.first(), tokens.last()) {
+        (Some(f), Some(l)) => f.span.start..l.span.end,
+        _ => at..at,
+    }
+}
+
+fn text<'a>(src: &'a str, tokens: &[Token]) -> &'a str {
+    match (tokens.first(), tokens.last()) {
+        (Some(f), Some(l)) => &src[f.span.start..l.span.end],
+        _ => "",
+    }
+}
+
+/// 1-based line and column of a byte offset.
+pub(crate) fn position(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(src: &str) -> OldMockMacro {
+        let mut m = parse_mock_macros(src);
+        assert_eq!(m.len(), 1);
+        m.remove(0)
+    }
+
+    #[test]
+    fn test_simple() {
+        let m = parse_one("MOCK_METHOD1(Foo, bool(int));");
+
+        assert_eq!(m.span, 0..29);
+        assert_eq!((m.line, m.column), (1, 1));
+        assert_eq!(m.macro_name, "MOCK_METHOD1");
+        assert_eq!(m.variant, Variant::default());
+        assert_eq!(m.arity, 1);
+        assert_eq!(m.name, "Foo");
+        assert_eq!(m.return_type, "bool");
+        assert_eq!(m.args, "int");
+        assert_eq!(m.args_span, 23..26);
+        assert_eq!(m.params, vec!["int"]);
+        assert_eq!(m.calltype, None);
+        assert!(m.semicolon);
+    }
+
+    #[test]
+    fn test_variant() {
+        let m = parse_one("MOCK_CONST_METHOD2_T_WITH_CALLTYPE(STDMETHODCALLTYPE, Foo, bool(int, T))");
+        let expected = Variant { is_const: true, is_template: true, with_calltype: true };
+
+        assert_eq!(m.variant, expected);
+        assert_eq!(m.arity, 2);
+        assert_eq!(m.calltype.as_deref(), Some("STDMETHODCALLTYPE"));
+        assert_eq!(m.name, "Foo");
+        assert_eq!(m.args, "int, T");
+        assert!(!m.semicolon);
+    }
+
+    #[test]
+    fn test_variant_display() {
+        let v = Variant { is_const: true, is_template: false, with_calltype: true };
+
+        assert_eq!(v.to_string(), "MOCK_CONST_METHODn_WITH_CALLTYPE");
+    }
+
+    #[test]
+    fn test_variant_from_str() {
+        let v = Variant { is_const: true, is_template: true, with_calltype: false };
+
+        assert_eq!("MOCK_CONST_METHODn_T".parse(), Ok(v));
+        assert!("MOCK_CONST_METHOD1_T".parse::<Variant>().is_err());
+        assert!("MOCK_METHOD".parse::<Variant>().is_err());
+    }
+
+    #[test]
+    fn test_position() {
+        let m = parse_one("class A {\n  MOCK_METHOD0(Foo, int());\n};");
+
+        assert_eq!((m.line, m.column), (2, 3));
+        assert_eq!(m.span, 12..37);
+    }
+
+    #[test]
+    fn test_multiline_args_kept() {
+        let m = parse_one("MOCK_METHOD2(Foo, bool(\n    int,\n    double));");
+
+        assert_eq!(m.args, "\n    int,\n    double");
+        assert_eq!(m.params, vec!["int", "double"]);
+    }
+
+    #[test]
+    fn test_unprotected_return_type() {
+        let m = parse_one("MOCK_METHOD0(GetPair, std::pair<bool, int>());");
+
+        assert_eq!(m.return_type, "std::pair<bool, int>");
+    }
+
+    #[test]
+    fn test_function_pointer_return_type() {
+        let m = parse_one("MOCK_METHOD1(GetCallback, void(*(int))(double, char));");
+
+        assert_eq!(m.return_type, "void(*)(double, char)");
+        assert_eq!(m.args, "int");
+
+        let m = parse_one("MOCK_METHOD0(GetCallback, void (* ()) (int));");
+
+        assert_eq!(m.return_type, "void (*) (int)");
+        assert_eq!(m.args, "");
+    }
+
+    #[test]
+    fn test_array_reference_return_type() {
+        let m = parse_one("MOCK_METHOD1(Get, int(&(int))[3]);");
+
+        assert_eq!(m.return_type, "int(&)[3]");
+        assert_eq!(m.args, "int");
+        assert_eq!(m.args_span, 24..27);
+    }
+
+    #[test]
+    fn test_member_function_pointer_return_type() {
+        let m = parse_one("MOCK_METHOD0(GetHandler, void (Foo::*())(int));");
+
+        assert_eq!(m.return_type, "void (Foo::*)(int)");
+        assert_eq!(m.args, "");
+    }
+
+    #[test]
+    fn test_parameter_declarators_are_parameters() {
+        let m = parse_one("MOCK_METHOD1(Register, std::function<void(int)>(void (*)(int)));");
+
+        assert_eq!(m.return_type, "std::function<void(int)>");
+        assert_eq!(m.args, "void (*)(int)");
+    }
+
+    #[test]
+    fn test_comments() {
+        let m = parse_one("MOCK_METHOD1_WITH_CALLTYPE /* a */ (/* b */ CT, /* c */ Foo /* d */, /* e */ bool /* f */ (int) /* g */);");
+
+        assert_eq!((m.name.as_str(), m.return_type.as_str(), m.calltype.as_deref()), ("Foo", "bool", Some("CT")));
+        assert_eq!(m.written, Written {
+            gap: " /* a */ ".to_owned(),
+            name: "/* c */ Foo /* d */".to_owned(),
+            return_type: "/* e */ bool /* f */ /* g */".to_owned(),
+            calltype: Some("/* b */ CT".to_owned()),
+        });
+    }
+
+    #[test]
+    fn test_missing_parentheses() {
+        let src = "MOCK_METHOD0;";
+
+        let expected = GmockSedError::UnsupportedConstruct { span: 0..12, construct: "macro name without an argument list" };
+
+        assert_eq!(parse(src, Scope::Code, &[]), vec![Err((0..12, expected))]);
+    }
+
+    #[test]
+    fn test_unmatched_parenthesis_does_not_hide_later_macros() {
+        let src = "MOCK_METHOD1(Foo, bool(int);\nMOCK_METHOD0(Bar, int());";
+        let found = parse(src, Scope::Code, &[]);
+
+        let expected = GmockSedError::UnmatchedParenthesis { span: 12..13, paren: '(' };
+
+        assert_eq!(found[0], Err((0..28, expected)));
+        assert!(found[1].is_ok());
+    }
+
+    #[test]
+    fn test_errors() {
+        use GmockSedError::*;
+
+        fn error(src: &str) -> (GmockSedError, &str) {
+            match parse(src, Scope::Code, &[]).remove(0) {
+                Err((_, e)) => { let span = e.span().unwrap(); (e, &src[span]) },
+                Ok(_) => panic!("{} parsed", src),
+            }
+        }
+
+        assert_eq!(error("MOCK_METHOD1(Foo);"), (UnparseableReturnType { span: 16..16, found: String::new() }, ""));
+        assert_eq!(error("MOCK_METHOD1(bool(int));"), (MissingMethodName { span: 13..13 }, ""));
+        assert_eq!(error("MOCK_METHOD1(, bool(int));"), (MissingMethodName { span: 13..13 }, ""));
+        assert_eq!(error("MOCK_METHOD0();"), (MissingMethodName { span: 13..13 }, ""));
+        assert_eq!(error("MOCK_METHOD1(Foo, bool);").1, "bool");
+        assert_eq!(error("MOCK_METHOD1(Foo, (int));").1, "(int)");
+        assert_eq!(error("MOCK_METHOD0_WITH_CALLTYPE(Foo)"), (MissingMethodName { span: 30..30 }, ""));
+        assert_eq!(error("MOCK_METHOD0(Foo, void(*)(int));").1, "*");
+        assert_eq!(error("MOCK_METHOD0(Foo, int[3]);").1, "int[3]");
+    }
+
+    #[test]
+    fn test_check_arity() {
+        let m = parse_one("MOCK_METHOD2(Foo, bool(int, std::map<int, int>));");
+        assert_eq!(m.params, vec!["int", "std::map<int, int>"]);
+        assert_eq!(m.check_arity(), Ok(()));
+        assert_eq!(parse_one("MOCK_METHOD0(Foo, bool(void));").check_arity(), Ok(()));
+
+        let m = parse_one("MOCK_METHOD3(Foo, bool(int, double));");
+        let expected = GmockSedError::ArityMismatch { span: 23..34, expected: 3, found: 2 };
+
+        assert_eq!(m.check_arity(), Err(expected));
+    }
+
+    #[test]
+    fn test_errors_are_skipped() {
+        let src = "MOCK_METHOD1(Foo);\nMOCK_METHOD0(Bar, int());";
+
+        assert_eq!(parse(src, Scope::Code, &[]).len(), 2);
+        assert_eq!(parse_mock_macros(src).len(), 1);
+    }
+}
@@ -1,28 +1,7 @@
-/// Static regex literals. Compiled to Regex structs elsewhere.
+//! Static regex literals. Compiled to Regex structs elsewhere.
 
-macro_rules! _macro_regex {
-    (*) => ( r"(MOCK_METHOD|MOCK_CONST_METHOD)(10|\d)" );
-    (!) => ( r"MOCK_(CONST_)?METHOD(10|\d)(_T)?(_WITH_CALLTYPE)?" );
-    (?) => ( r"MOCK_(?:CONST_)?METHOD(?:10|\d)(?:_T)?(?:_WITH_CALLTYPE)?" );
-}
-
-macro_rules! _mock_method_regex {
-    () => ( concat!(r"(", _macro_regex!(?), r")((?s)[^;]+)(;?)") );
-}
-
-macro_rules! _signature_regex {
-    () => ( r"([^,]+),([^\(]+)" );
-}
-
-pub const SEARCH_REGEX: &str = _macro_regex!(*);
-
-pub const REPLACE_REGEX: &str = _mock_method_regex!();
-
-pub const MACRO_REGEX: &str = _macro_regex!(!);
-
-pub const SIG_REGEX: &str = _signature_regex!();
-
-pub const CALLTYPE_REGEX: &str = r"[^,]+";
+/// Matches a whole identifier token.
+pub const MACRO_REGEX: &str = r"^MOCK_(CONST_)?METHOD(10|\d)(_T)?(_WITH_CALLTYPE)?$";
 
 
 #[cfg(test)]
@@ -31,84 +10,44 @@ mod tests {
     use regex::Regex;
     use lazy_static::lazy_static;
 
-    mod search_regex {
+    mod macro_regex {
         use super::*;
 
         fn regex() -> Regex {
             lazy_static! {
-                static ref RE: Regex = Regex::new(SEARCH_REGEX).unwrap();
+                static ref RE: Regex = Regex::new(MACRO_REGEX).unwrap();
             }
 
             RE.clone()
         }
 
         #[test]
-        fn test_match_on_old_style() {
-            let cpp = "MOCK_METHOD0(Foo, bool())";
+        fn test_match() {
+            let cpp = "MOCK_CONST_METHOD0";
 
             assert!(regex().is_match(cpp));
         }
 
         #[test]
         fn test_no_match_on_new_style() {
-            let cpp = "MOCK_METHOD(bool, Foo, ())";
+            let cpp = "MOCK_METHOD";
 
             assert!(!regex().is_match(cpp));
         }
 
         #[test]
-        fn test_correct_match_on_old_style_10() {
-            let cpp = "MOCK_METHOD10(...)";
-            let d = regex().captures(cpp).and_then(|c| c.get(2)).map(|m| m.as_str());
-
-            assert_eq!(d, Some("10"));
-        }
-    }
-
-    mod replace_regex {
-        use super::*;
-
-        fn regex() -> Regex {
-            lazy_static! {
-                static ref RE: Regex = Regex::new(REPLACE_REGEX).unwrap();
-            }
-
-            RE.clone()
-        }
-
-        #[test]
-        fn test_match() {
-            let cpp = "MOCK_METHOD0(Foo, bool())";
-
-            assert!(regex().is_match(cpp));
-        }
-
-        #[test]
-        fn test_groups() {
-            let cpp = "MOCK_METHOD0(Foo, bool())";
-            let c = regex().captures(cpp).unwrap();
-
-            assert_eq!(c.get(1).map(|m| m.as_str()), Some("MOCK_METHOD0"));
-            assert_eq!(c.get(2).map(|m| m.as_str()), Some("(Foo, bool())"));
-        }
-    }
-
-    mod macro_regex {
-        use super::*;
-
-        fn regex() -> Regex {
-            lazy_static! {
-                static ref RE: Regex = Regex::new(MACRO_REGEX).unwrap();
-            }
+        fn test_no_match_on_longer_identifier() {
+            let cpp = "MOCK_METHOD1Foo";
 
-            RE.clone()
+            assert!(!regex().is_match(cpp));
         }
 
         #[test]
-        fn test_match() {
-            let cpp = "MOCK_CONST_METHOD0";
+        fn test_correct_match_on_old_style_10() {
+            let cpp = "MOCK_METHOD10";
+            let d = regex().captures(cpp).and_then(|c| c.get(2)).map(|m| m.as_str());
 
-            assert!(regex().is_match(cpp));
+            assert_eq!(d, Some("10"));
         }
 
         #[test]
@@ -122,62 +61,4 @@ mod tests {
             assert_eq!(c.get(4).map(|m| m.as_str()), Some("_WITH_CALLTYPE"));
         }
     }
-
-    mod signature_regex {
-        use super::*;
-
-        fn regex() -> Regex {
-            lazy_static! {
-                static ref RE: Regex = Regex::new(SIG_REGEX).unwrap();
-            }
-
-            RE.clone()
-        }
-
-        #[test]
-        fn test_match() {
-            let cpp = "Foo, int(bool, double)";
-
-            assert!(regex().is_match(cpp));
-        }
-
-        #[test]
-        fn test_groups() {
-            let cpp = "Foo, int(bool, double)";
-            let c = regex().captures(cpp).unwrap();
-
-            assert_eq!(c.get(1).map(|m| m.as_str()), Some("Foo"));
-            assert_eq!(c.get(2).map(|m| m.as_str()), Some(" int"));
-        }
-
-        #[ignore]
-        #[test]
-        fn test_multiline() {
-            let cpp = "Foo, int(bool,\ndouble)";
-            let c = regex().captures(cpp).unwrap();
-
-            assert_eq!(c.get(3).map(|m| m.as_str()), Some("bool,\ndouble"));
-        }
-    }
-
-    mod calltype_regex {
-        use super::*;
-
-        fn regex() -> Regex {
-            lazy_static! {
-                static ref RE: Regex = Regex::new(CALLTYPE_REGEX).unwrap();
-            }
-
-            RE.clone()
-        }
-
-        #[test]
-        fn test_find() {
-            let cpp = "STDMETHODCALLTYPE, Foo, int(bool)";
-            let m = regex().find(cpp).unwrap();
-
-            assert_eq!(m.as_str(), "STDMETHODCALLTYPE");
-        }
-    }
 }
-
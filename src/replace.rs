@@ -1,54 +1,216 @@
 use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 use colored::*;
-use lazy_static::lazy_static;
-use regex::{Captures, Regex};
 
+use crate::alias::{recognise, Alias};
+use crate::conditions::{Condition, Conditions};
+use crate::defines;
 use crate::errors::GmockSedError;
-use crate::extract::{lextract, rextract};
-use crate::regexes::{REPLACE_REGEX, MACRO_REGEX, SIG_REGEX, CALLTYPE_REGEX};
-
-pub fn replace(src: &str, add_override: bool) -> ReplaceSummary {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(REPLACE_REGEX).unwrap();
-    }
+use crate::lexer::{tokenize, Scope, TokenKind};
+use crate::parse::{parse, position, OldMockMacro, Spanned, Variant};
+use crate::split;
+
+/// How `replace` converts macros. Build with `..Default::default()`, so that
+/// new options don't break existing callers.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ReplaceOptions<'a> {
+    /// Add 'override' to the qualifiers of every converted macro.
+    pub add_override: bool,
+    pub scope: Scope,
+    pub arity: Arity,
+    pub aliases: &'a [Alias],
+}
 
-    let mut err: Vec<String> = Vec::new();
+pub fn replace(src: &str, options: &ReplaceOptions) -> ReplaceSummary {
+    let ReplaceOptions { add_override, scope, arity, aliases } = *options;
+    let mut err: Vec<ReplaceError> = Vec::new();
+    let mut warnings = Vec::new();
+    let mut converted = Vec::new();
     let mut counter = 0;
 
-    let new = RE.replace_all(src, |caps: &Captures| {
+    let results: Vec<Spanned<OldMockMacro>> = parse(src, scope, aliases).into_iter().map(|result| {
+        let m = result?;
+
+        match (m.check_arity(), arity) {
+            (Err(e), Arity::Error) => Err((m.span.clone(), e)),
+            (Err(e), Arity::Warn) => { warnings.push(ReplaceError::new(src, m.span.clone(), e, aliases)); Ok(m) },
+            (Ok(()), _) => Ok(m),
+        }
+    }).collect();
+
+    // Branches of an #if are converted together or not at all
+    let conditions = Conditions::new(src);
+    let failed: Vec<(Range<usize>, Condition)> = results.iter()
+        .filter_map(|r| r.as_ref().err())
+        .flat_map(|(span, _)| conditions.at(span.start).into_iter().map(move |c| (span.clone(), c)))
+        .collect();
+
+    for result in results {
         counter += 1;
-        let original = &caps[0];
 
-        let parameters = match lextract(&caps[2].trim()) {
-            Ok(s) => s,
-            Err(e) => {
-                err.push(format!("  {}:\t{}", e, original));
-                return String::from(original)
-            },
-        };
+        let result = result.and_then(|m| {
+            let mine = conditions.at(m.span.start);
 
-        let q = Qualifiers::new(&caps[1], add_override).calltype(parameters);
+            match failed.iter().find(|(_, c)| mine.iter().any(|m| m.is_sibling(c))) {
+                Some((sibling, _)) => {
+                    let span = m.span.start..(m.span.start + m.macro_name.len());
+                    Err((m.span, GmockSedError::DivergentBranches { span, sibling: sibling.clone() }))
+                },
+                None => Ok(m),
+            }
+        });
 
-        let s = match Signature::new(q.strip_self(parameters).trim(), q.argc) {
-            Ok(s) => s,
-            Err(e) => {
-                err.push(format!("  {}:\t{}", e, original));
-                return String::from(original)
+        match result {
+            Ok(m) => {
+                let replacement = MockMethod::new(&m, add_override).to_string();
+                converted.push(Conversion { old: m, replacement });
             },
+            Err((span, e)) => err.push(ReplaceError::new(src, span, e, aliases)),
+        }
+    }
+
+    let edits = edits(src, &converted);
+    let new = splice(src, 0..src.len(), &edits);
+    let s = match new != src { true => Some(new), false => None };
+
+    ReplaceSummary { suggestion: s, total: counter, converted, edits, errors: err, warnings }
+}
+
+/// One edit per conversion, in source order. Those in a #define whose `\`
+/// continuations no longer line up are merged into one edit that realigns them.
+fn edits(src: &str, converted: &[Conversion]) -> Vec<Edit> {
+    let mut edits: Vec<Edit> = converted.iter().enumerate()
+        .map(|(i, c)| Edit { span: c.old.span.clone(), text: c.replacement.clone(), conversions: i..(i + 1) })
+        .collect();
+
+    for define in defines::defines(src) {
+        let inside = |e: &Edit| e.span.start < define.end && e.span.end > define.start;
+        let within = |e: &Edit| e.span.start >= define.start && e.span.end <= define.end;
+
+        let (first, last) = match (edits.iter().position(inside), edits.iter().rposition(inside)) {
+            (Some(first), Some(last)) => (first, last),
+            _ => continue,
         };
 
-        MockMethod::new(s, q, caps.get(3).map(|m| m.as_str())).to_string()
-    });
+        // A macro that doesn't end with the #define
+        if !edits[first..=last].iter().all(within) { continue }
+
+        let spliced = splice(src, define.clone(), &edits[first..=last]);
+        let realigned = defines::realign(&src[define.clone()], &spliced);
+        if realigned == spliced { continue }
 
-    let s = match new != src { true => Some(new.to_string()), false => None };
+        let conversions = edits[first].conversions.start..edits[last].conversions.end;
+        edits.splice(first..=last, [Edit { span: define, text: realigned, conversions }]);
+    }
+
+    edits
+}
+
+/// `src[span]` with `edits`, which lie inside it, applied.
+fn splice(src: &str, span: Range<usize>, edits: &[Edit]) -> String {
+    let mut new = String::with_capacity(span.len());
+    let mut last = span.start;
+
+    for e in edits {
+        new.push_str(&src[last..e.span.start]);
+        new.push_str(&e.text);
+        last = e.span.end;
+    }
 
-    ReplaceSummary { suggestion: s, total: counter, errors: err }
+    new.push_str(&src[last..span.end]);
+    new
+}
+
+/// What to do with a MOCK_METHODn that doesn't declare n parameters.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Arity {
+    /// Leave it as is, like any other macro that can't be converted.
+    #[default]
+    Error,
+    /// Convert it anyway, and list it in `ReplaceSummary::warnings`.
+    Warn,
+}
+
+impl FromStr for Arity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Arity::Error),
+            "warn" => Ok(Arity::Warn),
+            _ => Err(format!("invalid arity check '{}' (error, warn)", s)),
+        }
+    }
 }
 
 pub struct ReplaceSummary {
     pub suggestion: Option<String>,
     total: usize,
-    errors: Vec<String>,
+    converted: Vec<Conversion>,
+    edits: Vec<Edit>,
+    errors: Vec<ReplaceError>,
+    warnings: Vec<ReplaceError>,
+}
+
+/// Old-style macro and the new-style macro that replaces it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    pub old: OldMockMacro,
+    /// Replaces `old.span` in the source.
+    pub replacement: String,
+}
+
+/// Change made to the source: usually one conversion, but a whole #define when
+/// its `\` continuations had to be realigned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    /// Byte range replaced in the source.
+    pub span: Range<usize>,
+    pub text: String,
+    /// Indices into `ReplaceSummary::converted` of the conversions it makes.
+    pub conversions: Range<usize>,
+}
+
+/// Old-style macro that could not be converted.
+#[derive(Debug, PartialEq)]
+pub struct ReplaceError {
+    /// Byte range of the offending source.
+    pub span: Range<usize>,
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    pub macro_name: String,
+    pub variant: Variant,
+    pub error: GmockSedError,
+    pub snippet: String,
+}
+
+impl ReplaceError {
+    fn new(src: &str, span: Range<usize>, error: GmockSedError, aliases: &[Alias]) -> Self {
+        let (line, column) = position(src, span.start);
+        let snippet = &src[span.clone()];
+        // Errors are always spanned from the macro name
+        let macro_name = snippet.split(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or_default();
+        let variant = recognise(macro_name, aliases).map(|(variant, _, _)| variant).unwrap_or_default();
+
+        ReplaceError {
+            macro_name: macro_name.to_owned(),
+            variant,
+            snippet: snippet.to_owned(),
+            span,
+            line,
+            column,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "  {}:\t{}", self.error, self.snippet)
+    }
 }
 
 impl ReplaceSummary {
@@ -56,14 +218,43 @@ impl ReplaceSummary {
         self.errors.is_empty() && self.suggestion.is_some()
     }
 
+    /// At least one old-style macro was found, whether or not it converted.
+    pub fn is_match(&self) -> bool {
+        self.total > 0
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.errors.len()
+    }
+
     pub fn error_summary(&self) -> String {
-        self.errors.join("\n")
+        self.errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Macros that were converted, in source order.
+    pub fn converted(&self) -> &[Conversion] {
+        &self.converted
+    }
+
+    /// Non-overlapping edits that turn the source into `suggestion`, in source order.
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// Macros that could not be converted, in source order.
+    pub fn errors(&self) -> &[ReplaceError] {
+        &self.errors
+    }
+
+    /// Macros that were converted despite a problem, in source order.
+    pub fn warnings(&self) -> &[ReplaceError] {
+        &self.warnings
     }
 }
 
 impl fmt::Display for ReplaceSummary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.suggestion.is_none() {
+        if self.total == 0 {
             return write!(f, "{}",  "(0/0)".yellow())
         }
 
@@ -77,82 +268,43 @@ impl fmt::Display for ReplaceSummary {
     }
 }
 
-struct MockMethod {
-    _signature: Signature,
-    _qualifiers: Qualifiers,
-    _semicolon: bool,
+struct MockMethod<'m> {
+    _macro: &'m OldMockMacro,
+    _args: Args<'m>,
+    _qualifiers: Qualifiers<'m>,
 }
 
-impl MockMethod {
-    fn new(s: Signature, q: Qualifiers, sc: Option<&str>) -> Self {
+impl<'m> MockMethod<'m> {
+    fn new(m: &'m OldMockMacro, add_override: bool) -> Self {
         MockMethod {
-            _signature: s,
-            _qualifiers: q,
-            _semicolon: sc == Some(";")
+            _macro: m,
+            _args: Args::new(&m.args),
+            _qualifiers: Qualifiers::new(m, add_override),
         }
     }
 
     fn semicolon(&self) -> &'static str {
-        if self._semicolon { ";" } else { "" }
+        if self._macro.semicolon { ";" } else { "" }
     }
 }
 
-impl fmt::Display for MockMethod {
+impl<'m> fmt::Display for MockMethod<'m> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (s, q) = (self._signature.to_string(), self._qualifiers.to_string());
-
-        write!(f, "MOCK_METHOD({}{}){}", s, q, self.semicolon())
-    }
-}
-
-struct Signature {
-    _return: String,
-    _name: String,
-    _args: Args,
-}
+        let (m, w) = (self._macro, &self._macro.written);
+        let (r, n) = (protect_return(&m.return_type, &w.return_type), &w.name);
+        let (a, q) = (self._args.to_string(), self._qualifiers.to_string());
 
-impl Signature {
-    fn new(s: &str, argc: usize) -> Result<Self, GmockSedError> {
-        let args = Args::new(s, argc)?;
-        let rest = args.strip_self(s);
-
-        lazy_static! {
-            static ref RE: Regex = Regex::new(SIG_REGEX).unwrap();
-        }
-
-        if let Some(c) = RE.captures(rest.trim()) {
-            if let (Some(n), Some(r)) = (c.get(1), c.get(2)) {
-                return Ok(Signature {
-                    _return: protect(r.as_str().trim()),
-                    _name: n.as_str().trim().to_owned(),
-                    _args: args,
-                })
-            }
-        }
-
-        Err(GmockSedError::ParseSignatureError)
+        write!(f, "{}{}({}, {}, ({}){}){}", m.target, w.gap, r, n, a, q, self.semicolon())
     }
 }
 
-impl fmt::Display for Signature {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (r, n, a) = (&self._return, &self._name, self._args.to_string());
-        write!(f, "{}, {}, ({})", r, n, a)
-    }
-}
-
-struct Args {
-    args: String,
-    argc: usize,
+struct Args<'m> {
+    args: &'m str,
 }
 
-impl Args {
-    fn new(s: &str, argc: usize) -> Result<Self, GmockSedError> {
-        Ok(Args { args: rextract(s)?.to_owned(), argc: argc })
-    }
-
-    fn strip_self<'a>(&self, s: &'a str) -> &'a str {
-        &s[..(s.len() - (self.args.len() + 2))]
+impl<'m> Args<'m> {
+    fn new(args: &'m str) -> Self {
+        Args { args }
     }
 
     fn empty(&self) -> bool {
@@ -160,104 +312,56 @@ impl Args {
     }
 
     fn void(&self) -> bool {
-        self.args.trim() == "void"
+        split::is_void(self.args)
     }
 
-    fn protected(&self) -> String {
-        let mut p = String::new();
-        let mut n = 0;
-        let mut a = 0;
-
-        for (i, c) in self.args.chars().enumerate() {
-            match c {
-                ',' if n == 0 => {
-                    p.push_str(&protect(&self.args[a..i]));
-                    p.push(',');
-                    a = i + 1;
-                },
-                '<' => n += 1,
-                '>' => n -= 1,
-                _ => {},
-            }
-        }
+    /// `void` goes, but any comments around it stay.
+    fn void_comments(&self) -> String {
+        let tokens = tokenize(self.args, Scope::Code);
+        if !tokens.iter().any(|t| t.kind == TokenKind::Comment) { return String::new() }
 
-        p.push_str(&protect(&self.args[a..]));
-        p
-    }
-
-    fn contains_unprotected_comma(&self) -> bool {
-        let cc = self.comma_count();
-        let tc = self.trailing_comma();
-
-        self.argc != cc + (!tc as usize)
-    }
+        let void = tokens.iter().position(|t| t.kind == TokenKind::Ident).unwrap_or(tokens.len());
+        let space = tokens.get(void + 1).is_some_and(|t| t.kind == TokenKind::Whitespace);
+        let end = tokens.get(void + space as usize).map_or(self.args.len(), |t| t.span.end);
+        let start = tokens.get(void).map_or(end, |t| t.span.start);
 
-    fn comma_count(&self) -> usize {
-        self.args.chars().filter(|&c| c == ',').count()
+        format!("{}{}", &self.args[..start], &self.args[end..]).trim().to_owned()
     }
 
-    fn trailing_comma(&self) -> bool {
-        self.args.trim_end().ends_with(',')
+    fn protected(&self) -> String {
+        split::split(self.args).into_iter().map(|r| protect(&self.args[r])).collect::<Vec<_>>().join(",")
     }
-
 }
 
-impl fmt::Display for Args {
+impl<'m> fmt::Display for Args<'m> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.empty() || self.void() {
+        if self.empty() {
             write!(f, "")
-        } else if self.contains_unprotected_comma() {
-            write!(f, "{}", self.protected())
+        } else if self.void() {
+            write!(f, "{}", self.void_comments())
         } else {
-            write!(f, "{}", &self.args)
+            write!(f, "{}", self.protected())
         }
     }
 }
 
-#[derive(Debug)]
-struct Qualifiers {
+struct Qualifiers<'m> {
     _const: bool,
     _override: bool,
-    pub argc: usize,
-    _calltype: Option<String>,
+    _calltype: Option<&'m str>,
 }
 
-impl Qualifiers {
-    fn new(_macro: &str, add_override: bool) -> Self {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(MACRO_REGEX).unwrap();
-        }
-
-        let c = RE.captures(_macro).unwrap();
+impl<'m> Qualifiers<'m> {
+    fn new(m: &'m OldMockMacro, add_override: bool) -> Self {
         Qualifiers {
-            _const: c.get(1).is_some(),
+            _const: m.variant.is_const,
             _override: add_override,
-            argc: c.get(2).unwrap().as_str().parse::<usize>().unwrap(),
-            _calltype: c.get(4).map(|_| String::new()),
+            _calltype: m.written.calltype.as_deref(),
         }
     }
-
-    fn calltype(mut self, params: &str) -> Self {
-        if self._calltype.is_none() { return self }
-
-        lazy_static! {
-            static ref RE: Regex = Regex::new(CALLTYPE_REGEX).unwrap();
-        }
-
-        self._calltype = RE.find(params).map(|m| m.as_str().to_owned());
-        self
-    }
-
-    fn strip_self<'a>(&self, s: &'a str) -> &'a str {
-        &s[self.len()..]
-    }
-
-    fn len(&self) -> usize {
-        self._calltype.as_ref().map_or(0, |ct| ct.len())
-    }
 }
 
-impl fmt::Display for Qualifiers {
+impl<'m> fmt::Display for Qualifiers<'m> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match (self._const, self._override, self._calltype.as_ref()) {
             (false, false, None)     => write!(f, ""),
@@ -274,12 +378,21 @@ impl fmt::Display for Qualifiers {
 
 
 fn protect(s: &str) -> String {
-    if !s.contains(',') { return s.to_owned() }
+    if !split::needs_parens(s) { return s.to_owned() }
 
     let (lead, arg, trail) = trimmings(s);
     format!("{}({}){}", lead, arg, trail)
 }
 
+/// Function pointers and references to arrays, e.g. `void(*)(int)`, are
+/// wrapped too so that they read as a single type. `written` is `s` with comments.
+fn protect_return(s: &str, written: &str) -> String {
+    match s.ends_with(')') || s.ends_with(']') {
+        true => format!("({})", written),
+        false => protect(written),
+    }
+}
+
 fn trimmings(s: &str) -> (&str, &str, &str) {
     let not_whitespace = |c| { !char::is_whitespace(c) };
     let l = s.find(not_whitespace).unwrap();
@@ -0,0 +1,192 @@
+//! SARIF 2.1.0 logs for code-scanning tools.
+//!
+//! Each old-style macro is a result. Those that `replace` can convert carry a
+//! fix with the new-style macro.
+
+use std::ops::Range;
+use std::path::Path;
+use serde_json::{json, Value};
+
+use gmock_sed::{ReplaceSummary, Variant};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const COOKBOOK: &str = "https://github.com/google/googletest/blob/master/googlemock/docs/cook_book.md#old-style-mock_methodn-macros";
+
+/// (id, name, description)
+const RULES: [(&str, &str, &str); 4] = [
+    ("GMS001", "OldStyleMockMethod", "Old-style MOCK_METHODn macro"),
+    ("GMS002", "OldStyleMockConstMethod", "Old-style MOCK_CONST_METHODn macro"),
+    ("GMS003", "OldStyleMockMethodTemplate", "Old-style MOCK_METHODn_T macro"),
+    ("GMS004", "OldStyleMockMethodWithCalltype", "Old-style MOCK_METHODn_WITH_CALLTYPE macro"),
+];
+
+/// Most specific rule wins, e.g. MOCK_CONST_METHODn_T is reported as _T.
+fn rule(v: &Variant) -> usize {
+    match (v.is_const, v.is_template, v.with_calltype) {
+        (_, _, true) => 3,
+        (_, true, _) => 2,
+        (true, _, _) => 1,
+        _ => 0,
+    }
+}
+
+/// One result per old-style macro in `source`, as `replace` found them.
+pub fn results(path: &Path, source: &str, summary: &ReplaceSummary) -> Vec<Value> {
+    let uri = path.to_string_lossy().replace('\\', "/");
+
+    // Macros in a realigned #define share one fix, carried by the first
+    let converted = summary.edits().iter().flat_map(|e| {
+        let uri = &uri;
+
+        summary.converted()[e.conversions.clone()].iter().enumerate().map(move |(i, c)| {
+            let deleted = region(source, &e.span);
+            let region = region(source, &c.old.span);
+            let message = format!("{} can be replaced with {}", c.old.macro_name, c.old.target);
+            let mut r = result(uri, &c.old.variant, message, &region);
+
+            if i == 0 {
+                r["fixes"] = json!([{
+                    "description": { "text": format!("Convert to {}", c.old.target) },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": uri },
+                        "replacements": [{
+                            "deletedRegion": deleted,
+                            "insertedContent": { "text": e.text },
+                        }],
+                    }],
+                }]);
+            }
+
+            (c.old.span.start, r)
+        })
+    });
+
+    let failed = summary.errors().iter().map(|e| {
+        let region = region(source, &e.span);
+        let message = format!("{} can't be converted automatically: {}", e.macro_name, e.error);
+
+        (e.span.start, result(&uri, &e.variant, message, &region))
+    });
+
+    // Keep source order
+    let mut results: Vec<(usize, Value)> = converted.chain(failed).collect();
+    results.sort_by_key(|(start, _)| *start);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Complete log of a single run.
+pub fn log(results: Vec<Value>) -> Value {
+    let rules: Vec<Value> = RULES.iter().map(|(id, name, text)| json!({
+        "id": id,
+        "name": name,
+        "shortDescription": { "text": text },
+        "helpUri": COOKBOOK,
+        "defaultConfiguration": { "level": "warning" },
+    })).collect();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "gmock-sed",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/hamish-miller/gmock-sed",
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+fn result(uri: &str, variant: &Variant, message: String, region: &Value) -> Value {
+    let index = rule(variant);
+
+    json!({
+        "ruleId": RULES[index].0,
+        "ruleIndex": index,
+        "level": "warning",
+        "message": { "text": message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri },
+                "region": region,
+            },
+        }],
+    })
+}
+
+/// Region of `span`. Columns count characters, and the end column is exclusive.
+fn region(source: &str, span: &Range<usize>) -> Value {
+    let before = &source[..span.start];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    let text = &source[span.clone()];
+
+    let (end_line, end_column) = match text.rfind('\n') {
+        Some(i) => (line + text.matches('\n').count(), text[(i + 1)..].chars().count() + 1),
+        None => (line, column + text.chars().count()),
+    };
+
+    json!({
+        "startLine": line,
+        "startColumn": column,
+        "endLine": end_line,
+        "endColumn": end_column,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gmock_sed::ReplaceOptions;
+
+    fn results(path: &Path, source: &str) -> Vec<Value> {
+        super::results(path, source, &gmock_sed::replace(source, &ReplaceOptions::default()))
+    }
+
+    #[test]
+    fn test_rule_per_variant() {
+        let cpp = "MOCK_METHOD0(A, int());\nMOCK_CONST_METHOD0(B, int());\nMOCK_CONST_METHOD0_T(C, int());\nMOCK_METHOD0_WITH_CALLTYPE(ct, D, int());";
+        let ids: Vec<Value> = results(Path::new("a.h"), cpp).iter().map(|r| r["ruleId"].clone()).collect();
+
+        assert_eq!(ids, vec![json!("GMS001"), json!("GMS002"), json!("GMS003"), json!("GMS004")]);
+    }
+
+    #[test]
+    fn test_fix() {
+        let cpp = "class A {\n  MOCK_METHOD1(Foo, bool(\n      int));\n};";
+        let r = &results(Path::new("a.h"), cpp)[0];
+        let replacement = &r["fixes"][0]["artifactChanges"][0]["replacements"][0];
+
+        assert_eq!(r["locations"][0]["physicalLocation"]["region"], json!({
+            "startLine": 2, "startColumn": 3, "endLine": 3, "endColumn": 13,
+        }));
+        assert_eq!(replacement["insertedContent"]["text"], "MOCK_METHOD(bool, Foo, (\n      int));");
+    }
+
+    #[test]
+    fn test_define_fix_realigns() {
+        let cpp = "#define MOCKS             \\\n  MOCK_METHOD0(A, int()); \\\n  MOCK_METHOD0(B, int())\n";
+        let r = results(Path::new("a.h"), cpp);
+        let replacement = &r[0]["fixes"][0]["artifactChanges"][0]["replacements"][0];
+
+        assert_eq!(replacement["deletedRegion"], json!({ "startLine": 1, "startColumn": 1, "endLine": 3, "endColumn": 25 }));
+        assert_eq!(replacement["insertedContent"]["text"],
+                   "#define MOCKS              \\\n  MOCK_METHOD(int, A, ()); \\\n  MOCK_METHOD(int, B, ())");
+        assert_eq!(r[1]["fixes"], Value::Null);
+    }
+
+    #[test]
+    fn test_no_fix_for_errors() {
+        let r = &results(Path::new("a.h"), "MOCK_CONST_METHOD1(Foo);")[0];
+
+        assert_eq!(r["ruleId"], "GMS002");
+        assert_eq!(r["fixes"], Value::Null);
+        assert_eq!(r["locations"][0]["physicalLocation"]["region"]["endColumn"], 25);
+    }
+}
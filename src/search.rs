@@ -1,24 +1,43 @@
 use std::fmt;
-use lazy_static::lazy_static;
-use regex::Regex;
+use std::ops::Range;
 
-use crate::regexes::SEARCH_REGEX;
+use crate::alias::{recognise, Alias};
+use crate::conditions::{Condition, Conditions};
+use crate::lexer::{Lexer, Scope, TokenKind};
+use crate::parse::{position, Variant};
+
+/// Where `search` looks for macros. Build with `..Default::default()`, so that
+/// new options don't break existing callers.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SearchOptions<'a> {
+    pub scope: Scope,
+    pub aliases: &'a [Alias],
+}
+
+pub fn search(source: &str, mode: SearchMode, options: &SearchOptions) -> SearchSummary {
+    let mut macros = Lexer::new(source, options.scope)
+        .filter(|t| t.kind == TokenKind::Ident)
+        .filter_map(|t| recognise(t.text(source), options.aliases).map(|(variant, _, _)| (t.span, variant)));
 
-pub fn search(source: &str, mode: SearchMode) -> SearchSummary {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(SEARCH_REGEX).unwrap();
-    }
     use SearchMode::*;
     match mode {
-        Lazy => SearchSummary::from(RE.is_match(source)),
-        Full => SearchSummary::from(RE.find_iter(source).count()),
+        Lazy => SearchSummary::from(macros.next().is_some()),
+        Full => SearchSummary::from(macros.count()),
+        Positions => {
+            let conditions = Conditions::new(source);
+            SearchSummary::from(macros.map(|(span, variant)| SearchMatch::new(source, span, variant, &conditions)).collect::<Vec<_>>())
+        },
     }
 }
 
 #[derive(Copy, Clone)]
 pub enum SearchMode {
+    /// Stop at the first match.
     Lazy,
+    /// Count every match.
     Full,
+    /// Count every match and record where it is.
+    Positions,
 }
 
 impl From<bool> for SearchMode {
@@ -31,17 +50,62 @@ impl From<bool> for SearchMode {
 pub struct SearchSummary {
     pub is_match: bool,
     pub count: Option<usize>,
+    /// Only recorded with SearchMode::Positions.
+    pub matches: Vec<SearchMatch>,
 }
 
 impl From<bool> for SearchSummary {
     fn from(is_match: bool) -> Self {
-        SearchSummary { is_match: is_match, count: None }
+        SearchSummary { is_match, count: None, matches: Vec::new() }
     }
 }
 
 impl From<usize> for SearchSummary {
     fn from(count: usize) -> Self {
-        SearchSummary { is_match: count > 0, count: Some(count) }
+        SearchSummary { is_match: count > 0, count: Some(count), matches: Vec::new() }
+    }
+}
+
+impl From<Vec<SearchMatch>> for SearchSummary {
+    fn from(matches: Vec<SearchMatch>) -> Self {
+        SearchSummary { is_match: !matches.is_empty(), count: Some(matches.len()), matches }
+    }
+}
+
+/// Location of an old-style macro name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    /// Byte range of the macro name.
+    pub span: Range<usize>,
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    pub macro_name: String,
+    pub variant: Variant,
+    /// Enclosing #if branches, outermost first.
+    pub conditions: Vec<Condition>,
+}
+
+impl SearchMatch {
+    fn new(source: &str, span: Range<usize>, variant: Variant, conditions: &Conditions) -> Self {
+        let (line, column) = position(source, span.start);
+        let conditions = conditions.at(span.start);
+
+        SearchMatch { macro_name: source[span.clone()].to_owned(), span, line, column, variant, conditions }
+    }
+}
+
+impl fmt::Display for SearchMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ":{}:{}: {}", self.line, self.column, self.macro_name)?;
+
+        if !self.conditions.is_empty() {
+            let stack: Vec<String> = self.conditions.iter().map(ToString::to_string).collect();
+            write!(f, " [{}]", stack.join(" > "))?;
+        }
+
+        Ok(())
     }
 }
 
@@ -63,14 +127,51 @@ mod tests {
     fn test_singleline_macro() {
         let cpp = "MOCK_METHOD1(Foo, bool(int));";
 
-        assert!(search(&cpp, SearchMode::Lazy).is_match);
+        assert!(search(cpp, SearchMode::Lazy, &SearchOptions::default()).is_match);
+    }
+
+    #[test]
+    fn test_macro_prefix_is_not_a_match() {
+        let cpp = "MOCK_METHOD1_FOO(Foo, bool(int));";
+
+        assert!(!search(cpp, SearchMode::Lazy, &SearchOptions::default()).is_match);
     }
 
     #[test]
     fn test_multiline_macro() {
         let cpp = "MOCK_METHOD1\n(\nFoo,\nbool\n(int)\n);";
 
-        assert!(search(&cpp, SearchMode::Lazy).is_match);
+        assert!(search(cpp, SearchMode::Lazy, &SearchOptions::default()).is_match);
+    }
+
+    mod scope {
+        use super::*;
+
+        const CPP: &str = r#"
+// MOCK_METHOD1(Foo, bool(int));
+/* MOCK_METHOD1(Foo, bool(int)); */
+const char* s = "MOCK_METHOD1(Foo, bool(int));";
+const char* r = R"(MOCK_METHOD1(Foo, bool(int));)";
+#define MOCK MOCK_METHOD1(Foo, bool(int));
+MOCK_METHOD1(Foo, bool(int));
+"#;
+
+        #[test]
+        fn test_code_skips_comments_and_literals_but_not_defines() {
+            assert_eq!(search(CPP, SearchMode::Full, &SearchOptions::default()).count, Some(2));
+        }
+
+        #[test]
+        fn test_all_includes_comments_and_literals() {
+            assert_eq!(search(CPP, SearchMode::Full, &SearchOptions { scope: Scope::All, ..Default::default() }).count, Some(6));
+        }
+
+        #[test]
+        fn test_commented_out_only() {
+            let cpp = "// MOCK_METHOD1(Foo, bool(int));";
+
+            assert!(!search(cpp, SearchMode::Lazy, &SearchOptions::default()).is_match);
+        }
     }
 
     mod lazy {
@@ -79,25 +180,25 @@ mod tests {
         #[test]
         fn test_zero_match() {
             let cpp = "";
-            let expected = SearchSummary { is_match: false, count: None };
+            let expected = SearchSummary::from(false);
 
-            assert_eq!(search(&cpp, SearchMode::Lazy), expected);
+            assert_eq!(search(cpp, SearchMode::Lazy, &SearchOptions::default()), expected);
         }
 
         #[test]
         fn test_single_match() {
             let cpp = "MOCK_METHOD1(Foo, bool(int))";
-            let expected = SearchSummary { is_match: true, count: None };
+            let expected = SearchSummary::from(true);
 
-            assert_eq!(search(&cpp, SearchMode::Lazy), expected);
+            assert_eq!(search(cpp, SearchMode::Lazy, &SearchOptions::default()), expected);
         }
 
         #[test]
         fn test_multi_match() {
             let cpp = "MOCK_METHOD1(Foo, bool(int))\nMOCK_METHOD1(Bar, bool(int))";
-            let expected = SearchSummary { is_match: true, count: None };
+            let expected = SearchSummary::from(true);
 
-            assert_eq!(search(&cpp, SearchMode::Lazy), expected);
+            assert_eq!(search(cpp, SearchMode::Lazy, &SearchOptions::default()), expected);
         }
     }
 
@@ -107,25 +208,75 @@ mod tests {
         #[test]
         fn test_zero_match() {
             let cpp = "";
-            let expected = SearchSummary { is_match: false, count: Some(0) };
+            let expected = SearchSummary::from(0);
 
-            assert_eq!(search(&cpp, SearchMode::Full), expected);
+            assert_eq!(search(cpp, SearchMode::Full, &SearchOptions::default()), expected);
         }
 
         #[test]
         fn test_single_match() {
             let cpp = "MOCK_METHOD1(Foo, bool(int))";
-            let expected = SearchSummary { is_match: true, count: Some(1) };
+            let expected = SearchSummary::from(1);
 
-            assert_eq!(search(&cpp, SearchMode::Full), expected);
+            assert_eq!(search(cpp, SearchMode::Full, &SearchOptions::default()), expected);
         }
 
         #[test]
         fn test_multi_match() {
             let cpp = "MOCK_METHOD1(Foo, bool(int))\nMOCK_METHOD1(Bar, bool(int))";
-            let expected = SearchSummary { is_match: true, count: Some(2) };
+            let expected = SearchSummary::from(2);
+
+            assert_eq!(search(cpp, SearchMode::Full, &SearchOptions::default()), expected);
+        }
+    }
+
+    mod positions {
+        use super::*;
+
+        #[test]
+        fn test_positions() {
+            let cpp = "class A {\n  MOCK_METHOD1(Foo, bool(int));\n  MOCK_CONST_METHOD2_T(Bar, int(T, U));\n};";
+            let summary = search(cpp, SearchMode::Positions, &SearchOptions::default());
+
+            assert_eq!(summary.count, Some(2));
+            assert_eq!(summary.matches[0].to_string(), ":2:3: MOCK_METHOD1");
+            assert_eq!(summary.matches[1].to_string(), ":3:3: MOCK_CONST_METHOD2_T");
+            assert_eq!(summary.matches[1].span, 44..64);
+            assert_eq!(summary.matches[1].variant.to_string(), "MOCK_CONST_METHODn_T");
+        }
+
+        #[test]
+        fn test_column_counts_characters() {
+            let cpp = "/* é */ MOCK_METHOD0(Foo, int());";
+            let summary = search(cpp, SearchMode::Positions, &SearchOptions::default());
+
+            assert_eq!((summary.matches[0].line, summary.matches[0].column), (1, 9));
+        }
+
+        #[test]
+        fn test_conditions() {
+            let cpp = "#ifdef _WIN32
+MOCK_METHOD0_WITH_CALLTYPE(__stdcall, Foo, int());
+#else
+MOCK_METHOD0(Foo, int());
+#endif";
+            let summary = search(cpp, SearchMode::Positions, &SearchOptions::default());
+
+            assert_eq!(summary.matches[0].to_string(), ":2:1: MOCK_METHOD0_WITH_CALLTYPE [#ifdef _WIN32]");
+            assert_eq!(summary.matches[1].to_string(), ":4:1: MOCK_METHOD0 [#else (#ifdef _WIN32)]");
+            assert!(summary.matches[0].conditions[0].is_sibling(&summary.matches[1].conditions[0]));
+        }
+
+        #[test]
+        fn test_aliases() {
+            let aliases = [Alias::new("PROJ_MOCK_METHODn", Variant::default(), "MOCK_METHOD").unwrap()];
+            let cpp = "PROJ_MOCK_METHOD1(Foo, bool(int));\nMOCK_METHOD0(Bar, int());";
+            let summary = search(cpp, SearchMode::Positions, &SearchOptions { aliases: &aliases, ..Default::default() });
 
-            assert_eq!(search(&cpp, SearchMode::Full), expected);
+            assert_eq!(summary.matches[0].to_string(), ":1:1: PROJ_MOCK_METHOD1");
+            assert_eq!(summary.matches[0].variant.to_string(), "MOCK_METHODn");
+            assert_eq!(summary.count, Some(2));
+            assert_eq!(search(cpp, SearchMode::Full, &SearchOptions::default()).count, Some(1));
         }
     }
 }
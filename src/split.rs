@@ -0,0 +1,174 @@
+//! Split parameter lists at top-level commas.
+
+use std::ops::Range;
+
+use crate::lexer::{tokenize, Scope, Token, TokenKind};
+
+/// Byte ranges of the comma-separated parts of `src`, including whitespace and
+/// comments around them. Commas inside parentheses, brackets, braces or
+/// template arguments don't split, e.g. `std::map<int, int> m, void (*f)(int, int)`
+/// has two parts.
+pub(crate) fn split(src: &str) -> Vec<Range<usize>> {
+    let tokens = tokenize(src, Scope::Code);
+    let mut open: Vec<char> = Vec::new();
+    let mut parts = Vec::new();
+    let mut start = 0;
+
+    for (i, t) in tokens.iter().enumerate() {
+        match t.kind {
+            TokenKind::Punct(c @ ('(' | '[' | '{')) => open.push(c),
+            TokenKind::Punct(')') => close(&mut open, '('),
+            TokenKind::Punct(']') => close(&mut open, '['),
+            TokenKind::Punct('}') => close(&mut open, '{'),
+            // Inside parentheses it's a comparison, e.g. `Foo<(a < b)>`
+            TokenKind::Punct('<') if open.last() != Some(&'(') && !is_operator(&tokens, i) => open.push('<'),
+            // `>>` closes two templates
+            TokenKind::Punct('>') if open.last() == Some(&'<') && !is_operator(&tokens, i) => { open.pop(); },
+            TokenKind::Punct(',') if open.is_empty() => {
+                parts.push(start..t.span.start);
+                start = t.span.end;
+            },
+            _ => {},
+        }
+    }
+
+    parts.push(start..src.len());
+    parts
+}
+
+/// Has a comma outside parentheses, which the preprocessor would split a
+/// macro argument at, e.g. `std::map<int, int>` but not `void(int, int)`.
+pub(crate) fn needs_parens(src: &str) -> bool {
+    let mut depth = 0usize;
+
+    for t in tokenize(src, Scope::Code) {
+        match t.kind {
+            TokenKind::Punct('(') => depth += 1,
+            TokenKind::Punct(')') => depth = depth.saturating_sub(1),
+            TokenKind::Punct(',') if depth == 0 => return true,
+            _ => {},
+        }
+    }
+
+    false
+}
+
+/// Close the innermost `opening`. Any `<` left open were comparisons after all.
+fn close(open: &mut Vec<char>, opening: char) {
+    if let Some(i) = open.iter().rposition(|&c| c == opening) {
+        open.truncate(i);
+    }
+}
+
+/// `<` or `>` that is part of `<<`, `<=`, `>=` or `->`. `>>` is left to close templates.
+pub(crate) fn is_operator(tokens: &[Token], i: usize) -> bool {
+    let t = &tokens[i];
+    let prev = i.checked_sub(1).map(|p| &tokens[p]).filter(|p| p.span.end == t.span.start);
+    let next = tokens.get(i + 1).filter(|n| n.span.start == t.span.end);
+
+    let joined = |tok: Option<&Token>, c: char| tok.is_some_and(|tok| tok.is_punct(c));
+
+    match t.kind {
+        TokenKind::Punct('<') => joined(prev, '<') || joined(next, '<') || joined(next, '='),
+        _ => joined(prev, '-') || joined(next, '='),
+    }
+}
+
+/// Byte ranges of the parameters declared, without surrounding whitespace.
+/// `void` alone and a trailing comma don't count.
+pub(crate) fn parameters(src: &str) -> Vec<Range<usize>> {
+    let mut parts = split(src);
+
+    match parts.as_slice() {
+        [only] if code(&src[only.clone()]).is_empty() || is_void(&src[only.clone()]) => parts.clear(),
+        [.., last] if code(&src[last.clone()]).is_empty() => { parts.pop(); },
+        _ => {},
+    }
+
+    parts.into_iter().map(|r| trim(src, r)).collect()
+}
+
+/// Just `void`, give or take whitespace and comments.
+pub(crate) fn is_void(src: &str) -> bool {
+    code(src) == "void"
+}
+
+/// `r` without the whitespace around it.
+fn trim(src: &str, r: Range<usize>) -> Range<usize> {
+    let part = &src[r.clone()];
+    let start = r.start + (part.len() - part.trim_start().len());
+
+    start..(start + part.trim().len())
+}
+
+/// `src` without comments or surrounding whitespace.
+fn code(src: &str) -> String {
+    let tokens = tokenize(src, Scope::Code);
+    let code: String = tokens.iter().filter(|t| t.kind != TokenKind::Comment).map(|t| t.text(src)).collect();
+
+    code.trim().to_owned()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(src: &str) -> Vec<&str> {
+        split(src).into_iter().map(|r| &src[r]).collect()
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(parts("int a, bool b"), vec!["int a", " bool b"]);
+        assert_eq!(parts("std::map<int, int> m, void (*f)(int, int)"), vec!["std::map<int, int> m", " void (*f)(int, int)"]);
+        assert_eq!(parts(""), vec![""]);
+    }
+
+    #[test]
+    fn test_split_nested() {
+        assert_eq!(parts("std::function<void(int, int)> f, int"), vec!["std::function<void(int, int)> f", " int"]);
+        assert_eq!(parts("std::array<int, N> a, int"), vec!["std::array<int, N> a", " int"]);
+        assert_eq!(parts("Foo<(a > b), c> x, int"), vec!["Foo<(a > b), c> x", " int"]);
+        assert_eq!(parts("std::map<K, std::vector<V>> m, int"), vec!["std::map<K, std::vector<V>> m", " int"]);
+        assert_eq!(parts("Foo<Bar{1, 2}> x, int y[2] = {1, 2}"), vec!["Foo<Bar{1, 2}> x", " int y[2] = {1, 2}"]);
+        assert_eq!(parts("Foo<a[1, 2]> x, int"), vec!["Foo<a[1, 2]> x", " int"]);
+    }
+
+    #[test]
+    fn test_split_operators() {
+        assert_eq!(parts("int x = (a < b), int y"), vec!["int x = (a < b)", " int y"]);
+        assert_eq!(parts("Foo<(1 << 2), 3> x, int"), vec!["Foo<(1 << 2), 3> x", " int"]);
+        assert_eq!(parts("Foo<(a >= b)> x, int"), vec!["Foo<(a >= b)> x", " int"]);
+        assert_eq!(parts("std::function<auto() -> int> f, int"), vec!["std::function<auto() -> int> f", " int"]);
+    }
+
+    #[test]
+    fn test_needs_parens() {
+        assert!(needs_parens("std::map<int, int>"));
+        assert!(needs_parens("Foo<Bar{1, 2}>"));
+        assert!(!needs_parens("std::function<void(int, int)>"));
+        assert!(!needs_parens("const char* s = \"a, b\""));
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(parameters("").len(), 0);
+        assert_eq!(parameters("  void  ").len(), 0);
+        assert_eq!(parameters("/* nothing */ void").len(), 0);
+        assert_eq!(parameters("int").len(), 1);
+        assert_eq!(parameters("void*").len(), 1);
+        assert_eq!(parameters("int, double").len(), 2);
+        assert_eq!(parameters("\n    std::map<int, double>,\n    bool,\n").len(), 2);
+        assert_eq!(parameters("const char* s = \"a, b\", char c = ','").len(), 2);
+    }
+
+    #[test]
+    fn test_parameters() {
+        let src = "\n    std::map<int, double> m, /* flag */ bool,\n";
+        let params: Vec<&str> = parameters(src).into_iter().map(|r| &src[r]).collect();
+
+        assert_eq!(params, vec!["std::map<int, double> m", "/* flag */ bool"]);
+        assert!(parameters(" void ").is_empty());
+    }
+}
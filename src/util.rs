@@ -1,38 +1,174 @@
-use std::path::Path;
-use walkdir::DirEntry;
-use gmock_sed::ReplaceSummary;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use gmock_sed::{GmockSedError, ReplaceSummary};
+use crate::app::WalkOpts;
 
-pub fn read(path: &Path) -> String {
+pub fn read(path: &Path) -> Result<String, GmockSedError> {
     // Assume small file
-    std::fs::read_to_string(path).unwrap_or_else(|_e| {
-        #[cfg(debug_assertions)]
-        eprintln!("{}: {}", _e, path.display());
+    std::fs::read_to_string(path).map_err(|e| GmockSedError::io(path, &e))
+}
 
-        // Pretend the file is empty
-        String::new()
-    })
+pub fn write(path: &Path, contents: &ReplaceSummary) -> Result<(), GmockSedError> {
+    match contents.suggestion.as_ref() {
+        Some(code) => std::fs::write(path, code.as_bytes()).map_err(|e| GmockSedError::io(path, &e)),
+        None => Ok(()),
+    }
+}
+
+/// Walk `dir` in parallel, handing each C++ file to `visit` as soon as it's found.
+/// Honours .gitignore and .ignore files and skips hidden files, unless told otherwise.
+pub fn walk_cpp_files<F>(dir: &Path, opts: &WalkOpts, visit: F)
+where
+    F: Fn(Result<PathBuf, GmockSedError>) + Sync,
+{
+    let (include, exclude) = (globset(&opts.include), globset(&opts.exclude));
+
+    let selected = |entry: &DirEntry| {
+        let rel = entry.path().strip_prefix(dir).unwrap_or_else(|_| entry.path());
+        (opts.include.is_empty() || include.is_match(rel)) && !exclude.is_match(rel)
+    };
+
+    let walker = WalkBuilder::new(dir)
+        .max_depth(Some(opts.max_depth))
+        .standard_filters(!opts.no_ignore)
+        .hidden(!opts.hidden)
+        .build_parallel();
+
+    walker.run(|| {
+        let (visit, selected) = (&visit, &selected);
+
+        Box::new(move |entry| {
+            match entry {
+                Ok(e) if is_file(&e) && selected(&e) && is_cpp(e.path(), opts) => visit(Ok(e.into_path())),
+                Ok(_) => {},
+                Err(e) => visit(Err(walk_error(e))),
+            }
+
+            WalkState::Continue
+        })
+    });
+}
+
+/// All C++ files below `dir`, sorted. Traversal errors are collected in `failed`.
+pub fn cpp_files(dir: &Path, opts: &WalkOpts, failed: &mut Vec<GmockSedError>) -> Vec<PathBuf> {
+    let found = Mutex::new(Vec::new());
+    walk_cpp_files(dir, opts, |r| found.lock().unwrap().push(r));
+
+    let mut files = Vec::new();
+
+    for r in found.into_inner().unwrap() {
+        match r {
+            Ok(f) => files.push(f),
+            Err(e) => failed.push(e),
+        }
+    }
+
+    files.sort();
+    files
+}
+
+fn globset(globs: &[Glob]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for g in globs { builder.add(g.clone()); }
+
+    builder.build().expect("globs are validated by the argument parser")
+}
+
+fn walk_error(e: ignore::Error) -> GmockSedError {
+    let path = error_path(&e).unwrap_or_default();
+
+    match e.io_error() {
+        Some(io) => GmockSedError::io(&path, io),
+        None => GmockSedError::io(&path, &std::io::Error::other(e)),
+    }
+}
+
+fn error_path(e: &ignore::Error) -> Option<PathBuf> {
+    use ignore::Error::*;
+    match e {
+        WithPath { path, .. } => Some(path.clone()),
+        Loop { child, .. } => Some(child.clone()),
+        WithDepth { err, .. } | WithLineNumber { err, .. } => error_path(err),
+        Partial(errs) => errs.first().and_then(error_path),
+        _ => None,
+    }
+}
+
+/// `path` relative to `base`, with `.` dropped and `..` resolved lexically.
+/// Relative paths are taken from the current directory. A path outside `base`
+/// climbs out with `..`, unless they share no root (another drive).
+pub fn relative(path: &Path, base: &Path) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let (path, base) = (normalize(&cwd.join(path)), normalize(&cwd.join(base)));
+
+    let common = path.components().zip(base.components()).take_while(|(p, b)| p == b).count();
+    if common == 0 { return path }
+
+    let mut rel: PathBuf = base.components().skip(common).map(|_| std::path::Component::ParentDir).collect();
+    rel.extend(path.components().skip(common));
+    rel
 }
 
-pub fn write(path: &Path, contents: &ReplaceSummary) {
-    if let Some(code) = contents.suggestion.as_ref() {
-        std::fs::write(path, code.as_bytes()).unwrap()
+fn normalize(path: &Path) -> PathBuf {
+    use std::path::Component::*;
+
+    let mut normal = PathBuf::new();
+    for c in path.components() {
+        match c {
+            CurDir => {},
+            ParentDir => { normal.pop(); },
+            _ => normal.push(c),
+        }
     }
+
+    normal
 }
 
 pub fn is_file(entry: &DirEntry) -> bool {
-    entry.file_type().is_file()
+    entry.file_type().is_some_and(|t| t.is_file())
 }
 
-const CPP_SOURCE_EXT: [&'static str; 5] = ["cpp", ".cc", ".C", ".cxx", ".c++"];
-const CPP_HEADER_EXT: [&'static str; 6] = ["h", ".hh", ".H", ".hxx", ".hpp", ".h++"];
+const CPP_SOURCE_EXT: [&str; 5] = ["cpp", "cc", "C", "cxx", "c++"];
+const CPP_HEADER_EXT: [&str; 6] = ["h", "hh", "H", "hxx", "hpp", "h++"];
+
+/// Only C++ if the content says so.
+const CPP_SNIFF_EXT: [&str; 3] = ["inl", "ipp", "tcc"];
 
-pub fn is_cpp(entry: &DirEntry) -> bool {
-    if let Some(ext) = entry.path().extension().and_then(|os_str| os_str.to_str()) {
-        return CPP_SOURCE_EXT.contains(&ext) || CPP_HEADER_EXT.contains(&ext)
+pub fn is_cpp(path: &Path, opts: &WalkOpts) -> bool {
+    let ext = path.extension().map(|os_str| os_str.to_string_lossy());
+
+    match ext.as_deref() {
+        Some(ext) if is_known(ext, CPP_SOURCE_EXT.iter().chain(CPP_HEADER_EXT.iter()).copied()) => true,
+        Some(ext) if is_known(ext, opts.ext.iter().map(|e| e.trim_start_matches('.'))) => true,
+        Some(ext) if is_known(ext, CPP_SNIFF_EXT.iter().copied()) => opts.sniff && sniff(path),
+        None => opts.sniff && sniff(path),
+        // Better safe than sorry
+        Some(_) => false,
     }
+}
 
-    // Better safe than sorry
-    false
+/// `.c` is C but `.C` is C++. Otherwise case doesn't matter.
+fn is_known<'a>(ext: &str, mut known: impl Iterator<Item = &'a str>) -> bool {
+    known.any(|k| match k {
+        "C" | "c" => ext == k,
+        _ => ext.eq_ignore_ascii_case(k),
+    })
+}
+
+/// Guess from the first few KiB whether a file is C++ source.
+fn sniff(path: &Path) -> bool {
+    use std::io::Read;
+    const MARKERS: [&str; 7] = ["#include", "#pragma once", "#ifndef", "namespace", "class", "template", "MOCK_"];
+
+    let mut head = Vec::new();
+    let read = std::fs::File::open(path).and_then(|f| f.take(4096).read_to_end(&mut head));
+
+    if read.is_err() || head.contains(&0) { return false }
+
+    let head = String::from_utf8_lossy(&head);
+    MARKERS.iter().any(|m| head.contains(m))
 }
 
 #[macro_export]
@@ -43,3 +179,61 @@ macro_rules! fast_stdout {
         let mut $name = std::io::BufWriter::new(out.lock());
     };
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use structopt::StructOpt;
+
+    fn opts(args: &[&str]) -> WalkOpts {
+        WalkOpts::from_iter(std::iter::once("walk").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_extensions() {
+        let o = opts(&[]);
+
+        for f in ["a.cc", "a.hpp", "a.cxx", "a.h", "a.C", "a.H", "a.CPP", "a.Hpp"] {
+            assert!(is_cpp(Path::new(f), &o), "{}", f);
+        }
+
+        for f in ["a.c", "a.txt", "a.inl", "Makefile"] {
+            assert!(!is_cpp(Path::new(f), &o), "{}", f);
+        }
+    }
+
+    #[test]
+    fn test_extra_extensions() {
+        let o = opts(&["--ext", ".cu", "--ext", "ipp"]);
+
+        assert!(is_cpp(Path::new("a.cu"), &o));
+        assert!(is_cpp(Path::new("a.IPP"), &o));
+    }
+
+    #[test]
+    fn test_relative() {
+        let cwd = std::env::current_dir().unwrap();
+
+        assert_eq!(relative(Path::new("./mocks/a.h"), Path::new(".")), Path::new("mocks/a.h"));
+        assert_eq!(relative(Path::new("./mocks/./a.h"), Path::new("mocks")), Path::new("a.h"));
+        assert_eq!(relative(Path::new("mocks/../mocks/a.h"), Path::new("./mocks/")), Path::new("a.h"));
+        assert_eq!(relative(&cwd.join("mocks/a.h"), Path::new("mocks")), Path::new("a.h"));
+        assert_eq!(relative(Path::new("/a/b/c.h"), Path::new("/a/d/e")), Path::new("../../b/c.h"));
+        assert_eq!(relative(Path::new("/a/b"), Path::new("/a/b")), Path::new(""));
+    }
+
+    #[test]
+    fn test_sniff() {
+        let dir = tempfile::tempdir().unwrap();
+        let (header, readme, inl) = (dir.path().join("vector"), dir.path().join("README"), dir.path().join("a.inl"));
+        std::fs::write(&header, "#pragma once\nnamespace std {}").unwrap();
+        std::fs::write(&readme, "Nothing to see here").unwrap();
+        std::fs::write(&inl, "template <typename T> void f() {}").unwrap();
+
+        assert!(!is_cpp(&header, &opts(&[])));
+        assert!(is_cpp(&header, &opts(&["--sniff"])));
+        assert!(!is_cpp(&readme, &opts(&["--sniff"])));
+        assert!(is_cpp(&inl, &opts(&["--sniff"])));
+    }
+}
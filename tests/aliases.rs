@@ -0,0 +1,105 @@
+//! Macro spellings declared in config are searched for and converted like gMock's own.
+
+mod common;
+use common::*;
+
+mod aliases {
+use super::*;
+use predicates::str::contains;
+
+const CONFIG: &str = r#"
+[[aliases]]
+spelling = "PROJ_MOCK_CONST_METHODn_T"
+variant = "MOCK_CONST_METHODn_T"
+target = "PROJ_MOCK_METHOD"
+
+[[aliases]]
+spelling = "PROJ_MOCK_METHODn"
+variant = "MOCK_METHODn"
+"#;
+
+const OLD: &str = "class A {
+  PROJ_MOCK_CONST_METHOD1_T(Foo, bool(T));
+  PROJ_MOCK_METHOD2(Bar, void(int, double));
+  MOCK_METHOD0(Baz, int());
+};
+";
+
+#[test]
+fn test_search() {
+    let dir = tree(&[("a.h", OLD), ("gmock-sed.toml", CONFIG)]);
+    let config = dir.path().join("gmock-sed.toml");
+
+    binary().args(["search", "--config", config.to_str().unwrap(), "-n", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("a.h:2:3: PROJ_MOCK_CONST_METHOD1_T"))
+            .stdout(contains("a.h:3:3: PROJ_MOCK_METHOD2"))
+            .stdout(contains("a.h:4:3: MOCK_METHOD0"));
+}
+
+#[test]
+fn test_replace() {
+    let dir = tree(&[("a.h", OLD), ("gmock-sed.toml", CONFIG)]);
+    let config = dir.path().join("gmock-sed.toml");
+    let file = dir.path().join("a.h");
+
+    binary().args(["replace", "--config", config.to_str().unwrap(), file.to_str().unwrap()])
+            .assert()
+            .success();
+
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "class A {
+  PROJ_MOCK_METHOD(bool, Foo, (T), (const));
+  MOCK_METHOD(void, Bar, (int, double));
+  MOCK_METHOD(int, Baz, ());
+};
+");
+}
+
+#[test]
+fn test_export_fixes() {
+    let dir = tree(&[("a.h", OLD), ("gmock-sed.toml", CONFIG)]);
+    let config = dir.path().join("gmock-sed.toml");
+    let yaml = dir.path().join("fixes.yaml");
+
+    binary().args(["replace", "--config", config.to_str().unwrap(), "--export-fixes", yaml.to_str().unwrap(), dir.path().join("a.h").to_str().unwrap()])
+            .assert()
+            .success();
+
+    let fixes = std::fs::read_to_string(&yaml).unwrap();
+    assert!(fixes.contains("PROJ_MOCK_CONST_METHOD1_T can be replaced with PROJ_MOCK_METHOD\n"), "{}", fixes);
+    assert!(fixes.contains("PROJ_MOCK_METHOD2 can be replaced with MOCK_METHOD\n"), "{}", fixes);
+}
+
+#[test]
+fn test_check_arity() {
+    let dir = tree(&[("a.h", "PROJ_MOCK_METHOD2(Bar, void(int));\n"), ("gmock-sed.toml", CONFIG)]);
+    let config = dir.path().join("gmock-sed.toml");
+
+    binary().args(["check", "--config", config.to_str().unwrap(), dir.path().to_str().unwrap()])
+            .assert()
+            .code(2)
+            .stdout(contains("a.h:1: PROJ_MOCK_METHOD2 [ArityMismatch]"));
+}
+
+#[test]
+fn test_not_configured() {
+    let file = file("PROJ_MOCK_METHOD1(Foo, bool(int));\n");
+
+    binary().args(["search", file.to_str().unwrap()])
+            .assert()
+            .stdout("");
+}
+
+#[test]
+fn test_invalid_alias() {
+    let dir = tree(&[("gmock-sed.toml", "[[aliases]]\nspelling = \"PROJ_MOCK_METHOD\"\nvariant = \"MOCK_METHODn\"\n")]);
+    let config = dir.path().join("gmock-sed.toml");
+
+    binary().args(["search", "--config", config.to_str().unwrap(), dir.path().to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(contains("lowercase 'n'"));
+}
+
+}
@@ -0,0 +1,60 @@
+//! `--arity` chooses whether a MOCK_METHODn without n parameters is an error or a warning.
+
+mod common;
+use common::*;
+
+mod arity {
+use super::*;
+
+const MISMATCHED: &str = "class A {\n  MOCK_METHOD3(Foo, bool(int, std::map<int, int>));\n};";
+
+#[test]
+fn test_error_by_default() {
+    let file = file(MISMATCHED);
+
+    binary().args(["replace", "--show-errors", "--color", "never", file.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("error[E0005]: arity mismatch"))
+            .stdout(predicates::str::contains("expected 3 parameter(s), found 2"));
+
+    assert_eq!(read(&file), MISMATCHED);
+}
+
+#[test]
+fn test_warn() {
+    let file = file(MISMATCHED);
+
+    binary().args(["replace", "--arity", "warn", "--color", "never", file.to_str().unwrap()])
+            .assert()
+            .success()
+            .stderr(predicates::str::contains("warning[E0005]: arity mismatch"));
+
+    assert_eq!(read(&file), "class A {\n  MOCK_METHOD(bool, Foo, (int, (std::map<int, int>)));\n};");
+}
+
+#[test]
+fn test_matching_arity_is_quiet() {
+    let file = file("MOCK_METHOD2(Foo, bool(int, std::map<int, int>));");
+
+    binary().args(["replace", "--arity", "warn", file.to_str().unwrap()])
+            .assert()
+            .success()
+            .stderr("");
+}
+
+#[test]
+fn test_check() {
+    let dir = tree(&[("a.h", MISMATCHED)]);
+
+    binary().args(["check", dir.path().to_str().unwrap()])
+            .assert()
+            .code(2)
+            .stdout(predicates::str::contains("a.h:2: MOCK_METHOD3 [ArityMismatch]"));
+
+    binary().args(["check", "--arity", "warn", dir.path().to_str().unwrap()])
+            .assert()
+            .code(1);
+}
+
+}
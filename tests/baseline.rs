@@ -0,0 +1,101 @@
+//! `baseline` records legacy macros. `check --baseline` only fails when counts grow.
+
+mod common;
+use common::*;
+
+mod baseline {
+use super::*;
+
+const TWO: &str = "MOCK_METHOD0(Foo, int());\nMOCK_METHOD0(Bar, int());\n";
+const ONE: &str = "MOCK_METHOD0(Foo, int());\n";
+
+fn record(dir: &std::path::Path) -> std::path::PathBuf {
+    let path = dir.join("baseline.txt");
+
+    binary().args(["baseline", "-o", path.to_str().unwrap(), dir.join("src").to_str().unwrap()])
+            .assert()
+            .success();
+
+    path
+}
+
+fn check(dir: &std::path::Path, baseline: &std::path::Path) -> assert_cmd::assert::Assert {
+    binary().args(["check", "--baseline", baseline.to_str().unwrap(), dir.join("src").to_str().unwrap()])
+            .assert()
+}
+
+#[test]
+fn test_baseline_file() {
+    let dir = tree(&[("src/a.h", TWO), ("src/b.h", "")]);
+    let path = record(dir.path());
+
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "a.h:2\n");
+}
+
+#[test]
+fn test_legacy_tolerated() {
+    let dir = tree(&[("src/a.h", TWO)]);
+    let path = record(dir.path());
+
+    check(dir.path(), &path).code(0).stdout("");
+}
+
+#[test]
+fn test_count_grows() {
+    let dir = tree(&[("src/a.h", ONE)]);
+    let path = record(dir.path());
+    std::fs::write(dir.path().join("src/a.h"), TWO).unwrap();
+
+    check(dir.path(), &path).code(1).stdout(predicates::str::contains("a.h:2: MOCK_METHOD0"));
+}
+
+#[test]
+fn test_new_file() {
+    let dir = tree(&[("src/a.h", ONE)]);
+    let path = record(dir.path());
+    std::fs::write(dir.path().join("src/b.h"), ONE).unwrap();
+
+    check(dir.path(), &path).code(1).stdout(predicates::str::contains("b.h:1: MOCK_METHOD0"));
+}
+
+#[test]
+fn test_baseline_shrinks() {
+    let dir = tree(&[("src/a.h", TWO), ("src/b.h", ONE)]);
+    let path = record(dir.path());
+    std::fs::write(dir.path().join("src/a.h"), ONE).unwrap();
+    std::fs::write(dir.path().join("src/b.h"), "").unwrap();
+
+    check(dir.path(), &path).code(0);
+
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "a.h:1\n");
+}
+
+#[test]
+fn test_dir_spelled_differently() {
+    let dir = tree(&[("src/a.h", TWO), ("src/b.h", ONE)]);
+    let path = dir.path().join("baseline.txt");
+
+    binary().current_dir(dir.path())
+            .args(["baseline", "-o", "baseline.txt", "src"])
+            .assert()
+            .success();
+
+    binary().current_dir(dir.path())
+            .args(["check", "--baseline", "baseline.txt", "./src/"])
+            .assert()
+            .code(0)
+            .stdout("");
+
+    binary().args(["check", "--baseline", path.to_str().unwrap(), dir.path().join("src").to_str().unwrap()])
+            .assert()
+            .code(0);
+}
+
+#[test]
+fn test_missing_baseline() {
+    let dir = tree(&[("src/a.h", ONE)]);
+
+    check(dir.path(), &dir.path().join("missing.txt")).code(1);
+}
+
+}
@@ -0,0 +1,58 @@
+//! Macro boundaries come from balanced parentheses, not the next semicolon.
+
+mod common;
+use common::*;
+
+mod boundaries {
+use super::*;
+
+macro_rules! boundaries_test {
+    ($name:tt $old:tt -> $new:tt) => {
+        #[test]
+        fn $name() {
+            let path = file($old);
+
+            binary().args(&["replace", path.to_str().unwrap()])
+                    .assert()
+                    .success();
+
+            assert_eq!(read(&path), $new);
+        }
+    };
+}
+
+boundaries_test!(
+test_no_trailing_semicolon
+"
+MOCK_METHOD1(Foo, bool(int))
+int x;
+"
+->
+"
+MOCK_METHOD(bool, Foo, (int))
+int x;
+"
+);
+
+boundaries_test!(
+test_consecutive_without_semicolons
+"MOCK_METHOD0(Foo, bool()) MOCK_METHOD0(Bar, int())"
+->
+"MOCK_METHOD(bool, Foo, ()) MOCK_METHOD(int, Bar, ())"
+);
+
+boundaries_test!(
+test_parenthesis_in_literal
+"MOCK_METHOD1(Foo, bool(ARG(\")\")));"
+->
+"MOCK_METHOD(bool, Foo, (ARG(\")\")));"
+);
+
+boundaries_test!(
+test_calltype_whitespace
+"MOCK_METHOD1_WITH_CALLTYPE( STDMETHODCALLTYPE ,Foo, bool(int));"
+->
+"MOCK_METHOD(bool, Foo, (int), (Calltype(STDMETHODCALLTYPE)));"
+);
+
+}
@@ -0,0 +1,43 @@
+//! `check` exit codes: 0 clean, 1 old-style macros remain, 2 some would fail to convert.
+
+mod common;
+use common::*;
+
+mod check {
+use super::*;
+
+#[test]
+fn test_clean() {
+    let dir = tree(&[("a.h", "MOCK_METHOD(bool, Foo, (int));")]);
+
+    binary().args(["check", dir.path().to_str().unwrap()])
+            .assert()
+            .code(0)
+            .stdout("");
+}
+
+#[test]
+fn test_remaining() {
+    let dir = tree(&[("a.h", "class A {\n  MOCK_METHOD1(Foo, bool(int));\n};")]);
+    let expected = format!("{}:2: MOCK_METHOD1\n", dir.path().join("a.h").display());
+
+    binary().args(["check", dir.path().to_str().unwrap()])
+            .assert()
+            .code(1)
+            .stdout(expected);
+}
+
+#[test]
+fn test_unconvertible() {
+    let dir = tree(&[
+        ("a.h", "MOCK_METHOD1(Foo, bool(int));"),
+        ("b.h", "MOCK_METHOD1(Foo);"),
+    ]);
+
+    binary().args(["check", dir.path().to_str().unwrap()])
+            .assert()
+            .code(2)
+            .stdout(predicates::str::contains("b.h:1: MOCK_METHOD1 [UnparseableReturnType]"));
+}
+
+}
@@ -0,0 +1,114 @@
+//! Old-style macros in comments and string literals are left alone,
+//! unless --include-comments is given.
+
+mod common;
+use common::*;
+
+mod comments {
+use super::*;
+
+macro_rules! comments_test {
+    ($name:tt $old:tt -> $new:tt) => {
+        comments_test!($name [] $old -> $new);
+    };
+    (include $name:tt $old:tt -> $new:tt) => {
+        comments_test!($name ["--include-comments"] $old -> $new);
+    };
+    ($name:tt [$($flag:tt)*] $old:tt -> $new:tt) => {
+        #[test]
+        fn $name() {
+            let path = file($old);
+
+            binary().args(&["replace", $($flag,)* path.to_str().unwrap()])
+                    .assert()
+                    .success();
+
+            assert_eq!(read(&path), $new);
+        }
+    };
+}
+
+comments_test!(
+test_line_comment
+"
+// old: MOCK_METHOD1(Foo, bool(int))
+MOCK_METHOD1(Foo, bool(int));
+"
+->
+"
+// old: MOCK_METHOD1(Foo, bool(int))
+MOCK_METHOD(bool, Foo, (int));
+"
+);
+
+comments_test!(
+test_block_comment
+"
+/*
+ * MOCK_METHOD1(Foo, bool(int));
+ */
+MOCK_METHOD1(Foo, bool(int));
+"
+->
+"
+/*
+ * MOCK_METHOD1(Foo, bool(int));
+ */
+MOCK_METHOD(bool, Foo, (int));
+"
+);
+
+comments_test!(
+test_string_literal
+r#"
+const char* expected = "MOCK_METHOD1(Foo, bool(int));";
+MOCK_METHOD1(Foo, bool(int));
+"#
+->
+r#"
+const char* expected = "MOCK_METHOD1(Foo, bool(int));";
+MOCK_METHOD(bool, Foo, (int));
+"#
+);
+
+comments_test!(
+include
+test_include_comments
+"
+// old: MOCK_METHOD1(Foo, bool(int))
+MOCK_METHOD1(Foo, bool(int));
+"
+->
+"
+// old: MOCK_METHOD(bool, Foo, (int))
+MOCK_METHOD(bool, Foo, (int));
+"
+);
+
+comments_test!(
+test_non_ascii_comment_in_arguments
+"
+// 古いマクロ
+MOCK_METHOD2(Foo, bool(std::map<int, int> /* 地図 */, int /* 数 */));
+"
+->
+"
+// 古いマクロ
+MOCK_METHOD(bool, Foo, ((std::map<int, int> /* 地図 */), int /* 数 */));
+"
+);
+
+comments_test!(
+test_comments_inside_macro_are_kept
+"
+MOCK_METHOD2(Send, bool(const Msg& m /* owned */, int timeout_ms)); // retries
+MOCK_CONST_METHOD1(/* legacy */ Get, /* nullable */ Foo*(int));
+"
+->
+"
+MOCK_METHOD(bool, Send, (const Msg& m /* owned */, int timeout_ms)); // retries
+MOCK_METHOD(/* nullable */ Foo*, /* legacy */ Get, (int), (const));
+"
+);
+
+}
@@ -1,8 +1,9 @@
-/// Helper functions for testing.
+//! Helper functions for testing.
+#![allow(dead_code)]
 
 use std::io::prelude::*;
 use assert_cmd::Command;
-use tempfile::{NamedTempFile, TempPath};
+use tempfile::{NamedTempFile, TempDir, TempPath};
 
 pub fn binary() -> Command {
     Command::cargo_bin("gmock-sed").unwrap()
@@ -10,11 +11,24 @@ pub fn binary() -> Command {
 
 pub fn file(contents: &str) -> TempPath {
     let mut file = NamedTempFile::new().unwrap();
-    file.write(contents.as_bytes()).unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
 
     file.into_temp_path()
 }
 
+/// Temporary directory containing the given (name, contents) files.
+pub fn tree(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+
+    for (name, contents) in files {
+        let path = dir.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    dir
+}
+
 pub fn read(path: &TempPath) -> String {
     std::fs::read_to_string(path).unwrap()
 }
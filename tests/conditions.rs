@@ -0,0 +1,78 @@
+//! Macros inside #if branches: `search` reports the branch, `replace` converts
+//! every branch of an #if or none of them.
+
+mod common;
+use common::*;
+
+mod conditions {
+use super::*;
+
+const CPP: &str = "\
+class MockFoo {
+#ifdef _WIN32
+  MOCK_METHOD0_WITH_CALLTYPE(__stdcall, Foo, int());
+#else
+  MOCK_METHOD0(Foo, int);
+#endif
+};
+";
+
+#[test]
+fn test_search_reports_conditions() {
+    let dir = tree(&[("a.h", CPP)]);
+    let a = dir.path().join("a.h");
+    let expected = format!(
+        "{0}:3:3: MOCK_METHOD0_WITH_CALLTYPE [#ifdef _WIN32]\n{0}:5:3: MOCK_METHOD0 [#else (#ifdef _WIN32)]\n",
+        a.display(),
+    );
+
+    binary().args(["search", "--line-numbers", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(expected);
+}
+
+#[test]
+fn test_search_json_conditions() {
+    let dir = tree(&[("a.h", CPP)]);
+
+    binary().args(["search", "--format", "jsonl", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(r##""conditions":[{"group":"#ifdef _WIN32","branch":"#else"}]"##));
+}
+
+#[test]
+fn test_sibling_failure_blocks_conversion() {
+    let path = file(CPP);
+
+    binary().args(["replace", "--show-errors", "--color", "never", path.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("error[E0007]: divergent branches"))
+            .stdout(predicates::str::contains("2 macro(s) in 1 file(s) could not be converted"));
+
+    assert_eq!(read(&path), CPP);
+}
+
+#[test]
+fn test_branches_convert_together() {
+    let path = file(&CPP.replace("MOCK_METHOD0(Foo, int);", "MOCK_METHOD0(Foo, int());"));
+
+    binary().args(["replace", path.to_str().unwrap()])
+            .assert()
+            .success();
+
+    assert!(read(&path).contains("#ifdef _WIN32\n  MOCK_METHOD(int, Foo, (), (Calltype(__stdcall)));\n#else\n  MOCK_METHOD(int, Foo, ());\n#endif"));
+}
+
+#[test]
+fn test_sarif_has_no_fix_for_blocked_sibling() {
+    let dir = tree(&[("a.h", CPP)]);
+    let output = binary().args(["search", "--format", "sarif", dir.path().to_str().unwrap()]).output().unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(log["runs"][0]["results"][0]["fixes"], serde_json::Value::Null);
+}
+
+}
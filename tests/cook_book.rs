@@ -1,6 +1,6 @@
-/// Examples specified in the gMock cookbook.
-///
-/// https://github.com/google/googletest/blob/master/googlemock/docs/cook_book.md#old-style-mock_methodn-macros
+//! Examples specified in the gMock cookbook.
+//!
+//! https://github.com/google/googletest/blob/master/googlemock/docs/cook_book.md#old-style-mock_methodn-macros
 
 mod common;
 use common::*;
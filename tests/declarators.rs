@@ -0,0 +1,59 @@
+//! Return types that wrap the parameter list, e.g. functions returning function pointers.
+
+mod common;
+use common::*;
+
+mod declarators {
+use super::*;
+
+macro_rules! declarators_test {
+    ($name:tt $old:tt -> $new:tt) => {
+        #[test]
+        fn $name() {
+            let path = file($old);
+
+            binary().args(&["replace", path.to_str().unwrap()])
+                    .assert()
+                    .success();
+
+            assert_eq!(read(&path), $new);
+        }
+    };
+}
+
+declarators_test!(
+test_function_pointer
+"MOCK_METHOD0(GetCallback, void(*())(int));"
+->
+"MOCK_METHOD((void(*)(int)), GetCallback, ());"
+);
+
+declarators_test!(
+test_array_reference
+"MOCK_METHOD1(Get, int(&(int))[3]);"
+->
+"MOCK_METHOD((int(&)[3]), Get, (int));"
+);
+
+declarators_test!(
+test_member_function_pointer
+"MOCK_CONST_METHOD1(GetHandler, void (Foo::*(const std::string&))(int, int));"
+->
+"MOCK_METHOD((void (Foo::*)(int, int)), GetHandler, (const std::string&), (const));"
+);
+
+#[test]
+fn test_pointer_without_parameters() {
+    let cpp = "MOCK_METHOD0(GetCallback, void(*)(int));";
+    let path = file(cpp);
+
+    binary().args(["replace", "--show-errors", "--color", "never", path.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("error[E0006]: unsupported construct"))
+            .stdout(predicates::str::contains("declare a `using` alias"));
+
+    assert_eq!(read(&path), cpp);
+}
+
+}
@@ -0,0 +1,83 @@
+//! Old-style macros in #define bodies are converted, keeping `\` continuations lined up.
+
+mod common;
+use common::*;
+
+mod defines {
+use super::*;
+
+macro_rules! defines_test {
+    ($name:tt $old:tt -> $new:tt) => {
+        #[test]
+        fn $name() {
+            let path = file($old);
+
+            binary().args(&["replace", path.to_str().unwrap()])
+                    .assert()
+                    .success();
+
+            assert_eq!(read(&path), $new);
+        }
+    };
+}
+
+defines_test!(
+test_single_line
+"#define DECLARE_MOCK_GETTER(T, Name) MOCK_CONST_METHOD0(Name, T());\n"
+->
+"#define DECLARE_MOCK_GETTER(T, Name) MOCK_METHOD(T, Name, (), (const));\n"
+);
+
+defines_test!(
+test_continuations_stay_aligned
+"
+#define DECLARE_MOCK_PROPERTY(T, Name)          \\
+  MOCK_CONST_METHOD0(Get##Name, T());           \\
+  MOCK_METHOD1(Set##Name, void(T))
+"
+->
+"
+#define DECLARE_MOCK_PROPERTY(T, Name)          \\
+  MOCK_METHOD(T, Get##Name, (), (const));       \\
+  MOCK_METHOD(void, Set##Name, (T))
+"
+);
+
+defines_test!(
+test_continuations_pushed_right
+"
+#define MOCK_PAIR(K, V)                 \\
+  MOCK_METHOD0(Get, std::pair<K, V>()); \\
+  MOCK_METHOD0(Clear, void())
+"
+->
+"
+#define MOCK_PAIR(K, V)                    \\
+  MOCK_METHOD((std::pair<K, V>), Get, ()); \\
+  MOCK_METHOD(void, Clear, ())
+"
+);
+
+defines_test!(
+test_macro_split_across_continuations
+"
+#define MOCK_SEND                 \\
+  MOCK_METHOD2(Send, bool(        \\
+      const Msg& m, int timeout))
+"
+->
+"
+#define MOCK_SEND                 \\
+  MOCK_METHOD(bool, Send, (       \\
+      const Msg& m, int timeout))
+"
+);
+
+defines_test!(
+test_other_directives_are_skipped
+"#if MOCK_METHOD1(Foo, bool(int))\n#endif\n"
+->
+"#if MOCK_METHOD1(Foo, bool(int))\n#endif\n"
+);
+
+}
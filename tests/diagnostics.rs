@@ -0,0 +1,28 @@
+//! `replace --show-errors` prints a diagnostic for each macro that couldn't be converted.
+
+mod common;
+use common::*;
+
+mod diagnostics {
+use super::*;
+
+#[test]
+fn test_show_errors() {
+    let dir = tree(&[("a.h", "class A {\n  MOCK_METHOD1(Foo, bool(int));\n  MOCK_CONST_METHOD1(, bool(int));\n};")]);
+    let a = dir.path().join("a.h");
+    let expected = format!("\
+error[E0003]: missing method name
+ --> {}:3:22
+  |
+3 |   MOCK_CONST_METHOD1(, bool(int));
+  |                      ^ expected a method name
+", a.display());
+
+    binary().args(["replace", "--show-errors", "--color", "never", a.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(expected))
+            .stdout(predicates::str::contains("1 macro(s) in 1 file(s) could not be converted"));
+}
+
+}
@@ -0,0 +1,101 @@
+//! `replace --diff` prints a patch instead of writing files.
+
+mod common;
+use common::*;
+
+mod diff {
+use super::*;
+
+const OLD: &str = "class A {\n  MOCK_METHOD1(Foo, bool(int));\n};\n";
+
+#[test]
+fn test_unified_diff() {
+    let dir = tree(&[("a.h", OLD)]);
+    let path = dir.path().join("a.h");
+
+    let expected =
+"--- a/a.h
++++ b/a.h
+@@ -1,3 +1,3 @@
+ class A {
+-  MOCK_METHOD1(Foo, bool(int));
++  MOCK_METHOD(bool, Foo, (int));
+ };
+";
+
+    binary().current_dir(dir.path())
+            .args(["replace", "--diff", "--color", "never", path.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(expected);
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), OLD);
+}
+
+#[test]
+fn test_git_apply_walked_directory() {
+    let dir = tree(&[("mocks/a.h", OLD), ("mocks/b.h", OLD)]);
+
+    let output = binary().current_dir(dir.path())
+                         .args(["replace", "--diff", "--color", "never", "."])
+                         .output()
+                         .unwrap();
+    let patch = String::from_utf8(output.stdout).unwrap();
+
+    assert!(patch.contains("--- a/mocks/a.h\n+++ b/mocks/a.h\n"), "{}", patch);
+
+    std::fs::write(dir.path().join("mocks.patch"), patch).unwrap();
+    let check = std::process::Command::new("git")
+        .current_dir(dir.path())
+        .args(["apply", "--check", "mocks.patch"])
+        .output()
+        .unwrap();
+
+    assert!(check.status.success(), "{}", String::from_utf8_lossy(&check.stderr));
+}
+
+#[test]
+fn test_file_outside_current_directory() {
+    let dir = tree(&[("outside.h", OLD), ("cwd/README", "")]);
+    let cwd = dir.path().join("cwd");
+
+    let output = binary().current_dir(&cwd)
+                         .args(["replace", "--diff", "--color", "never", "../outside.h"])
+                         .output()
+                         .unwrap();
+    let patch = String::from_utf8(output.stdout).unwrap();
+
+    assert!(patch.starts_with("--- a/../outside.h\n+++ b/../outside.h\n"), "{}", patch);
+
+    std::fs::write(cwd.join("outside.patch"), patch).unwrap();
+    let check = std::process::Command::new("git")
+        .current_dir(&cwd)
+        .args(["apply", "--unsafe-paths", "--check", "outside.patch"])
+        .output()
+        .unwrap();
+
+    assert!(check.status.success(), "{}", String::from_utf8_lossy(&check.stderr));
+}
+
+#[test]
+fn test_no_diff_when_unchanged() {
+    let path = file("MOCK_METHOD(bool, Foo, (int));\n");
+
+    binary().args(["replace", "--diff", path.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout("");
+}
+
+#[test]
+fn test_errors_go_to_stderr() {
+    let path = file("MOCK_METHOD1(Foo);\n");
+
+    binary().args(["replace", "--diff", "--show-errors", path.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout("")
+            .stderr(predicates::str::contains("error[E0004]: unparseable return type"));
+}
+
+}
@@ -0,0 +1,95 @@
+//! `replace --export-fixes` writes clang-apply-replacements YAML instead of changing files.
+
+mod common;
+use common::*;
+use serde_yaml::Value;
+
+mod export_fixes {
+use super::*;
+
+const CPP: &str = "class A {\n  MOCK_METHOD1(Foo, bool(\n    int));\n  MOCK_CONST_METHOD0(Bar, std::map<int, int>());\n};\n";
+const NEW: &str = "class A {\n  MOCK_METHOD(bool, Foo, (\n    int));\n  MOCK_METHOD((std::map<int, int>), Bar, (), (const));\n};\n";
+
+/// Apply the exported replacements back to front, the way clang-apply-replacements would.
+fn apply(source: &str, fixes: &Value) -> String {
+    let mut new = source.to_owned();
+    let mut replacements: Vec<&Value> = fixes["Diagnostics"].as_sequence().unwrap().iter()
+        .flat_map(|d| d["DiagnosticMessage"]["Replacements"].as_sequence().unwrap())
+        .collect();
+    replacements.sort_by_key(|r| std::cmp::Reverse(r["Offset"].as_u64().unwrap()));
+
+    for r in replacements {
+        let offset = r["Offset"].as_u64().unwrap() as usize;
+        let length = r["Length"].as_u64().unwrap() as usize;
+        new.replace_range(offset..(offset + length), r["ReplacementText"].as_str().unwrap());
+    }
+
+    new
+}
+
+#[test]
+fn test_export() {
+    let dir = tree(&[("a.h", CPP)]);
+    let yaml = dir.path().join("fixes.yaml");
+
+    binary().args(["replace", "--export-fixes", yaml.to_str().unwrap(), dir.path().to_str().unwrap()])
+            .assert()
+            .success();
+
+    let fixes: Value = serde_yaml::from_str(&std::fs::read_to_string(&yaml).unwrap()).unwrap();
+    let path = std::fs::canonicalize(dir.path().join("a.h")).unwrap();
+
+    assert_eq!(std::fs::read_to_string(dir.path().join("a.h")).unwrap(), CPP);
+    assert_eq!(fixes["MainSourceFile"], "");
+    assert_eq!(fixes["Diagnostics"][0]["DiagnosticMessage"]["FilePath"], path.to_str().unwrap());
+    assert_eq!(fixes["Diagnostics"][0]["DiagnosticMessage"]["Replacements"][0]["Offset"], 12);
+    assert_eq!(apply(CPP, &fixes), NEW);
+}
+
+#[test]
+fn test_define_continuations_stay_aligned() {
+    let cpp = "#define DECLARE_MOCK_PROPERTY(T, Name)          \\\n  MOCK_CONST_METHOD0(Get##Name, T());           \\\n  MOCK_METHOD1(Set##Name, void(T))\nint x;\nMOCK_METHOD0(Foo, int());\n";
+    let dir = tree(&[("a.h", cpp), ("b.h", cpp)]);
+    let yaml = dir.path().join("fixes.yaml");
+
+    binary().args(["replace", "--export-fixes", yaml.to_str().unwrap(), dir.path().join("a.h").to_str().unwrap()])
+            .assert()
+            .success();
+    binary().args(["replace", dir.path().join("b.h").to_str().unwrap()])
+            .assert()
+            .success();
+
+    let fixes: Value = serde_yaml::from_str(&std::fs::read_to_string(&yaml).unwrap()).unwrap();
+    let replaced = std::fs::read_to_string(dir.path().join("b.h")).unwrap();
+
+    assert!(replaced.contains("  MOCK_METHOD(T, Get##Name, (), (const));       \\\n"), "{}", replaced);
+    assert_eq!(fixes["Diagnostics"].as_sequence().unwrap().len(), 3);
+    assert_eq!(apply(cpp, &fixes), replaced);
+}
+
+#[test]
+fn test_files_with_errors_are_left_out() {
+    let dir = tree(&[("a.h", CPP), ("b.h", "MOCK_METHOD0(Foo, int());\nMOCK_METHOD1(Bar);")]);
+    let yaml = dir.path().join("fixes.yaml");
+
+    binary().args(["replace", "--export-fixes", yaml.to_str().unwrap(), dir.path().to_str().unwrap()])
+            .assert()
+            .success();
+
+    let fixes: Value = serde_yaml::from_str(&std::fs::read_to_string(&yaml).unwrap()).unwrap();
+
+    assert_eq!(fixes["Diagnostics"].as_sequence().unwrap().len(), 2);
+}
+
+#[test]
+fn test_unwritable() {
+    let dir = tree(&[("a.h", CPP)]);
+    let yaml = dir.path().join("missing").join("fixes.yaml");
+
+    binary().args(["replace", "--export-fixes", yaml.to_str().unwrap(), dir.path().to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("fixes.yaml"));
+}
+
+}
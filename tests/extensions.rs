@@ -0,0 +1,67 @@
+//! Which files count as C++ when walking a directory.
+
+mod common;
+use common::*;
+use predicates::prelude::*;
+use predicates::str::contains;
+
+mod extensions {
+use super::*;
+
+const OLD: &str = "MOCK_METHOD1(Foo, bool(int));";
+
+#[test]
+fn test_default_extensions() {
+    let dir = tree(&[("a.cc", OLD), ("b.hpp", OLD), ("c.CPP", OLD), ("d.c", OLD)]);
+
+    binary().args(["search", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("a.cc").and(contains("b.hpp")).and(contains("c.CPP")))
+            .stdout(contains("d.c\n").not());
+}
+
+#[test]
+fn test_ext_flag() {
+    let dir = tree(&[("a.cu", OLD)]);
+
+    binary().args(["search", "--ext", "cu", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("a.cu"));
+}
+
+#[test]
+fn test_config_file() {
+    let dir = tree(&[("src/a.cu", OLD), ("src/vector", "#pragma once\n"), ("gmock-sed.toml", "extensions = [\".cu\"]\n")]);
+    let config = dir.path().join("gmock-sed.toml");
+
+    binary().args(["search", "--config", config.to_str().unwrap(), dir.path().join("src").to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("a.cu"));
+}
+
+#[test]
+fn test_default_config_file() {
+    let dir = tree(&[("src/a.ipp", OLD), (".gmock-sed.toml", "sniff = true\n")]);
+
+    binary().current_dir(dir.path())
+            .args(["search", "src"])
+            .assert()
+            .success()
+            .stdout(contains("a.ipp"));
+}
+
+#[test]
+fn test_invalid_config_file() {
+    let dir = tree(&[("gmock-sed.toml", "sniff = 1\n")]);
+    let config = dir.path().join("gmock-sed.toml");
+
+    binary().args(["search", "--config", config.to_str().unwrap(), dir.path().to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(contains("gmock-sed.toml"));
+}
+
+}
@@ -0,0 +1,48 @@
+//! Each result stays attached to the file it came from.
+
+mod common;
+use common::*;
+use predicates::str::contains;
+
+mod file_outcome {
+use super::*;
+
+#[test]
+fn test_empty_file_does_not_shift_results() {
+    let empty = file("");
+    let mock = file("MOCK_METHOD1(Foo, bool(int));");
+
+    binary().args(["replace", empty.to_str().unwrap(), mock.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("skipped (empty file)"));
+
+    assert_eq!(read(&empty), "");
+    assert_eq!(read(&mock), "MOCK_METHOD(bool, Foo, (int));");
+}
+
+#[test]
+fn test_unreadable_file_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.h");
+    let mock = file("MOCK_METHOD1(Foo, bool(int));");
+
+    binary().args(["replace", missing.to_str().unwrap(), mock.to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(contains("missing.h"));
+
+    assert_eq!(read(&mock), "MOCK_METHOD(bool, Foo, (int));");
+}
+
+#[test]
+fn test_all_failed_counts_every_macro() {
+    let mock = file("#ifdef _WIN32\nMOCK_METHOD0_WITH_CALLTYPE(__stdcall, Foo);\n#else\nMOCK_METHOD0(Foo);\n#endif\n");
+
+    binary().args(["replace", mock.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("(0/2) not written"));
+}
+
+}
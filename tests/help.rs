@@ -0,0 +1,32 @@
+//! Each subcommand keeps its own description in `--help`.
+
+mod common;
+use common::*;
+
+mod help {
+use super::*;
+use predicates::prelude::*;
+
+#[test]
+fn test_subcommand_descriptions() {
+    binary().arg("--help")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("search      Locate files that contain"))
+            .stdout(predicate::str::contains("baseline    Record the current per-file counts"))
+            .stdout(predicate::str::contains("replace     Substitute old-style macros"))
+            .stdout(predicate::str::contains("Directory traversal").not());
+}
+
+#[test]
+fn test_subcommand_help() {
+    for (cmd, about) in [("search", "Locate files"), ("baseline", "Record the current"), ("replace", "Substitute old-style")] {
+        binary().args([cmd, "--help"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(about))
+                .stdout(predicate::str::contains("Directory traversal").not());
+    }
+}
+
+}
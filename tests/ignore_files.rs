@@ -0,0 +1,55 @@
+//! Directory traversal honours ignore files and skips hidden files by default.
+
+mod common;
+use common::*;
+use predicates::prelude::*;
+use predicates::str::contains;
+
+mod ignore_files {
+use super::*;
+
+const OLD: &str = "MOCK_METHOD1(Foo, bool(int));";
+
+fn search(dir: &tempfile::TempDir, flags: &[&str]) -> assert_cmd::assert::Assert {
+    binary().arg("search")
+            .args(flags)
+            .arg(dir.path())
+            .assert()
+            .success()
+}
+
+#[test]
+fn test_gitignore() {
+    let dir = tree(&[(".git/HEAD", ""), (".gitignore", "build/\n"), ("build/a.h", OLD), ("src/b.h", OLD)]);
+
+    search(&dir, &[]).stdout(contains("b.h").and(contains("a.h").not()));
+    search(&dir, &["--no-ignore"]).stdout(contains("a.h"));
+}
+
+#[test]
+fn test_ignore_file_outside_git() {
+    let dir = tree(&[(".ignore", "third_party/\n"), ("third_party/a.h", OLD), ("src/b.h", OLD)]);
+
+    search(&dir, &[]).stdout(contains("b.h").and(contains("a.h").not()));
+}
+
+#[test]
+fn test_hidden() {
+    let dir = tree(&[(".cache/a.h", OLD), ("src/b.h", OLD)]);
+
+    search(&dir, &[]).stdout(contains("b.h").and(contains("a.h").not()));
+    search(&dir, &["--hidden"]).stdout(contains("a.h"));
+}
+
+#[test]
+fn test_replace_honours_ignore_files() {
+    let dir = tree(&[(".ignore", "vendor/\n"), ("vendor/a.h", OLD)]);
+
+    binary().args(["replace", dir.path().to_str().unwrap()])
+            .assert()
+            .success();
+
+    assert_eq!(std::fs::read_to_string(dir.path().join("vendor/a.h")).unwrap(), OLD);
+}
+
+}
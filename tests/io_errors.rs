@@ -0,0 +1,53 @@
+//! I/O problems are reported per file on stderr. Other files are still processed.
+
+mod common;
+use common::*;
+use predicates::str::contains;
+
+mod io_errors {
+use super::*;
+
+#[test]
+fn test_replace_invalid_utf8() {
+    let bad = file("");
+    std::fs::write(&bad, b"MOCK_METHOD0(Foo, int());\xff").unwrap();
+    let mock = file("MOCK_METHOD1(Foo, bool(int));");
+
+    binary().args(["replace", bad.to_str().unwrap(), mock.to_str().unwrap()])
+            .assert()
+            .code(1)
+            .stderr(contains("valid UTF-8"));
+
+    assert_eq!(read(&mock), "MOCK_METHOD(bool, Foo, (int));");
+}
+
+#[test]
+fn test_search_invalid_utf8() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("bad.h"), b"MOCK_METHOD0(Foo, int());\xff").unwrap();
+    std::fs::write(dir.path().join("good.h"), "MOCK_METHOD0(Foo, int());").unwrap();
+
+    binary().args(["search", dir.path().to_str().unwrap()])
+            .assert()
+            .code(1)
+            .stdout(contains("good.h"))
+            .stderr(contains("bad.h"));
+}
+
+#[test]
+fn test_search_missing_dir() {
+    binary().args(["search", "does/not/exist"])
+            .assert()
+            .code(1)
+            .stderr(contains("does/not/exist"));
+}
+
+#[test]
+fn test_replace_missing_file_reports_os_message() {
+    binary().args(["replace", "nonexist.h"])
+            .assert()
+            .code(1)
+            .stderr(contains("nonexist.h: No such file or directory"));
+}
+
+}
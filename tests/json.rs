@@ -0,0 +1,89 @@
+//! `--format json` and `--format jsonl` emit one record per file.
+
+mod common;
+use common::*;
+use serde_json::Value;
+
+mod json {
+use super::*;
+
+fn records(stdout: &[u8]) -> Vec<Value> {
+    std::str::from_utf8(stdout).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+#[test]
+fn test_search_jsonl() {
+    let dir = tree(&[("a.h", "class A {\n  MOCK_CONST_METHOD1_T(Foo, bool(T));\n};"), ("b.h", "")]);
+    let output = binary().args(["search", "--format", "jsonl", dir.path().to_str().unwrap()]).output().unwrap();
+    let records = records(&output.stdout);
+
+    assert!(output.status.success());
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["path"], dir.path().join("a.h").to_str().unwrap());
+    assert_eq!(records[0]["count"], 1);
+    assert_eq!(records[0]["occurrences"][0]["variant"], "MOCK_CONST_METHODn_T");
+    assert_eq!(records[0]["occurrences"][0]["line"], 2);
+    assert_eq!(records[0]["occurrences"][0]["column"], 3);
+    assert_eq!(records[0]["occurrences"][0]["span"]["start"], 12);
+}
+
+#[test]
+fn test_search_json_is_an_array() {
+    let dir = tree(&[("a.h", "MOCK_METHOD0(Foo, int());"), ("b.h", "MOCK_METHOD0(Bar, int());")]);
+    let output = binary().args(["search", "--format", "json", dir.path().to_str().unwrap()]).output().unwrap();
+    let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(value.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_search_json_no_match() {
+    let dir = tree(&[("a.h", "")]);
+
+    binary().args(["search", "--format", "json", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout("[]\n");
+}
+
+#[test]
+fn test_replace_jsonl() {
+    let dir = tree(&[("a.h", "MOCK_METHOD1(Foo, bool(int));\nMOCK_METHOD1(Bar);")]);
+    let output = binary().args(["replace", "--format", "jsonl", dir.path().to_str().unwrap()]).output().unwrap();
+    let records = records(&output.stdout);
+
+    assert!(output.status.success());
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["status"], "unchanged");
+    assert_eq!(records[0]["converted"], 1);
+    assert_eq!(records[0]["failed"], 1);
+    assert_eq!(records[0]["occurrences"][0]["macro_name"], "MOCK_METHOD1");
+    assert_eq!(records[0]["errors"][0]["kind"], "UnparseableReturnType");
+    assert_eq!(records[0]["errors"][0]["line"], 2);
+    assert_eq!(records[0]["errors"][0]["snippet"], "MOCK_METHOD1(Bar);");
+}
+
+#[test]
+fn test_replace_json_written() {
+    let dir = tree(&[("a.h", "MOCK_METHOD1(Foo, bool(int));")]);
+    let output = binary().args(["replace", "--format", "json", dir.path().to_str().unwrap()]).output().unwrap();
+    let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(value[0]["status"], "written");
+    assert_eq!(value[0]["errors"], Value::Array(vec![]));
+    assert_eq!(std::fs::read_to_string(dir.path().join("a.h")).unwrap(), "MOCK_METHOD(bool, Foo, (int));");
+}
+
+#[test]
+fn test_replace_unreadable() {
+    let dir = tree(&[]);
+    let missing = dir.path().join("missing.h");
+    let output = binary().args(["replace", "--format", "jsonl", missing.to_str().unwrap()]).output().unwrap();
+    let records = records(&output.stdout);
+
+    assert!(!output.status.success());
+    assert_eq!(records[0]["status"], "unreadable");
+    assert_eq!(records[0]["errors"][0]["kind"], "IoError");
+}
+
+}
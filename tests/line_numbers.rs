@@ -0,0 +1,46 @@
+//! `search --line-numbers` lists every macro as path:line:column, optionally with context.
+
+mod common;
+use common::*;
+
+mod line_numbers {
+use super::*;
+
+const CPP: &str = "class A {\n  MOCK_METHOD1(Foo, bool(int));\n  MOCK_CONST_METHOD2_T(Bar, int(T, U));\n};";
+
+#[test]
+fn test_each_occurrence() {
+    let dir = tree(&[("a.h", CPP), ("b.h", "MOCK_METHOD(bool, Foo, (int));")]);
+    let a = dir.path().join("a.h");
+    let expected = format!("{0}:2:3: MOCK_METHOD1\n{0}:3:3: MOCK_CONST_METHOD2_T\n", a.display());
+
+    binary().args(["search", "-n", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(expected);
+}
+
+#[test]
+fn test_context() {
+    let dir = tree(&[("a.h", CPP)]);
+    let expected = format!("\
+{0}-1-class A {{
+{0}:2:3:  MOCK_METHOD1(Foo, bool(int));
+{0}:3:3:  MOCK_CONST_METHOD2_T(Bar, int(T, U));
+{0}-4-}};
+", dir.path().join("a.h").display());
+
+    binary().args(["search", "-C", "1", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(expected);
+}
+
+#[test]
+fn test_conflicts_with_count() {
+    binary().args(["search", "-n", "-c", "."])
+            .assert()
+            .failure();
+}
+
+}
@@ -0,0 +1,73 @@
+//! Arbitrary, mostly broken, Unicode never makes the library panic or point
+//! inside a character.
+
+use proptest::prelude::*;
+
+use gmock_sed::{Arity, ReplaceOptions, Scope, SearchMode, SearchOptions};
+
+/// Fragments of old-style macros mixed with multi-byte characters, so that
+/// spans often end up next to them.
+fn cpp() -> impl Strategy<Value = String> {
+    let fragment = prop_oneof![
+        Just("MOCK_METHOD1"), Just("MOCK_CONST_METHOD2_T"), Just("MOCK_METHOD0_WITH_CALLTYPE"),
+        Just("("), Just(")"), Just(","), Just("<"), Just(">"), Just("*"), Just("&"), Just("["), Just("]"),
+        Just("{"), Just("}"), Just(";"), Just(" "), Just("\n"), Just("\\\n"), Just("#if X\n"), Just("#define M(T) "),
+        Just("//"), Just("/*"), Just("*/"), Just("\""), Just("'"), Just("void"), Just("bool"),
+        Just("日本語"), Just("é"), Just("\u{1F600}"), Just("\t"),
+    ];
+
+    prop_oneof![
+        prop::collection::vec(fragment, 0..48).prop_map(|f| f.concat()),
+        any::<String>(),
+    ]
+}
+
+/// 1-based line and column of a byte offset, the way the library reports them.
+fn position(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset];
+
+    (before.matches('\n').count() + 1, before.rsplit('\n').next().unwrap_or_default().chars().count() + 1)
+}
+
+fn scope() -> impl Strategy<Value = Scope> {
+    any::<bool>().prop_map(Scope::from)
+}
+
+fn arity() -> impl Strategy<Value = Arity> {
+    prop_oneof![Just(Arity::Error), Just(Arity::Warn)]
+}
+
+proptest! {
+    #[test]
+    fn replace_never_panics(src in cpp(), add_override: bool, scope in scope(), arity in arity()) {
+        let summary = gmock_sed::replace(&src, &ReplaceOptions { add_override, scope, arity, ..Default::default() });
+
+        for c in summary.converted() {
+            prop_assert!(src.is_char_boundary(c.old.span.start) && src.is_char_boundary(c.old.span.end));
+            prop_assert!(src.is_char_boundary(c.old.args_span.start) && src.is_char_boundary(c.old.args_span.end));
+            prop_assert_eq!(position(&src, c.old.span.start), (c.old.line, c.old.column));
+        }
+
+        for e in summary.errors().iter().chain(summary.warnings()) {
+            let span = e.error.span().unwrap();
+
+            prop_assert!(src.is_char_boundary(span.start) && src.is_char_boundary(span.end));
+            prop_assert_eq!(&src[e.span.clone()], &e.snippet);
+            prop_assert_eq!(position(&src, e.span.start), (e.line, e.column));
+        }
+    }
+
+    #[test]
+    fn search_never_panics(src in cpp(), scope in scope()) {
+        let summary = gmock_sed::search(&src, SearchMode::Positions, &SearchOptions { scope, ..Default::default() });
+
+        for m in summary.matches.iter() {
+            prop_assert_eq!(&src[m.span.clone()], &m.macro_name);
+        }
+    }
+
+    #[test]
+    fn check_never_panics(src in cpp(), scope in scope(), arity in arity()) {
+        gmock_sed::check(&src, &ReplaceOptions { scope, arity, ..Default::default() });
+    }
+}
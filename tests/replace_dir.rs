@@ -0,0 +1,76 @@
+//! `replace` walks directories like `search`, with glob filters.
+
+mod common;
+use common::*;
+use predicates::prelude::*;
+
+mod replace_dir {
+use super::*;
+
+const OLD: &str = "MOCK_METHOD1(Foo, bool(int));";
+const NEW: &str = "MOCK_METHOD(bool, Foo, (int));";
+
+fn contents(dir: &tempfile::TempDir, name: &str) -> String {
+    std::fs::read_to_string(dir.path().join(name)).unwrap()
+}
+
+#[test]
+fn test_directory() {
+    let dir = tree(&[("a.h", OLD), ("sub dir/b.h", OLD), ("c.txt", OLD)]);
+
+    binary().args(["replace", dir.path().to_str().unwrap()])
+            .assert()
+            .success();
+
+    assert_eq!(contents(&dir, "a.h"), NEW);
+    assert_eq!(contents(&dir, "sub dir/b.h"), NEW);
+    assert_eq!(contents(&dir, "c.txt"), OLD);
+}
+
+#[test]
+fn test_max_depth() {
+    let dir = tree(&[("a.h", OLD), ("sub/b.h", OLD)]);
+
+    binary().args(["replace", "--max-depth", "1", dir.path().to_str().unwrap()])
+            .assert()
+            .success();
+
+    assert_eq!(contents(&dir, "a.h"), NEW);
+    assert_eq!(contents(&dir, "sub/b.h"), OLD);
+}
+
+#[test]
+fn test_include_exclude() {
+    let dir = tree(&[("mocks/a.h", OLD), ("mocks/third_party/b.h", OLD), ("src/c.h", OLD)]);
+
+    binary().args(["replace", "--include", "mocks/**", "--exclude", "**/third_party/**", dir.path().to_str().unwrap()])
+            .assert()
+            .success();
+
+    assert_eq!(contents(&dir, "mocks/a.h"), NEW);
+    assert_eq!(contents(&dir, "mocks/third_party/b.h"), OLD);
+    assert_eq!(contents(&dir, "src/c.h"), OLD);
+}
+
+#[test]
+fn test_only_flagged_files_reported() {
+    let dir = tree(&[("a.h", OLD), ("b.h", NEW)]);
+
+    binary().args(["replace", "--dry-run", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("a.h"))
+            .stdout(predicates::str::contains("b.h").not());
+}
+
+#[test]
+fn test_all_files_reported() {
+    let dir = tree(&[("a.h", OLD), ("b.h", NEW)]);
+
+    binary().args(["replace", "--dry-run", "--all-files", dir.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("b.h"));
+}
+
+}
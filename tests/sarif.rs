@@ -0,0 +1,70 @@
+//! `search --format sarif` and `check --format sarif` emit a SARIF 2.1.0 log.
+
+mod common;
+use common::*;
+use serde_json::Value;
+
+mod sarif {
+use super::*;
+
+fn log(args: &[&str]) -> (Value, Option<i32>) {
+    let output = binary().args(args).output().unwrap();
+    (serde_json::from_slice(&output.stdout).unwrap(), output.status.code())
+}
+
+#[test]
+fn test_search() {
+    let dir = tree(&[("a.h", "class A {\n  MOCK_CONST_METHOD1(Foo, bool(int));\n};")]);
+    let (log, code) = log(&["search", "--format", "sarif", dir.path().to_str().unwrap()]);
+    let run = &log["runs"][0];
+    let result = &run["results"][0];
+
+    assert_eq!(code, Some(0));
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 4);
+    assert_eq!(result["ruleId"], "GMS002");
+    assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startLine"], 2);
+    assert_eq!(result["fixes"][0]["artifactChanges"][0]["replacements"][0]["insertedContent"]["text"],
+               "MOCK_METHOD(bool, Foo, (int), (const));");
+}
+
+#[test]
+fn test_check_keeps_exit_codes() {
+    let dir = tree(&[("a.h", "MOCK_METHOD1(Foo, bool(int));"), ("b.h", "MOCK_METHOD1_T(Foo);")]);
+    let (log, code) = log(&["check", "--format", "sarif", dir.path().to_str().unwrap()]);
+    let results = log["runs"][0]["results"].as_array().unwrap();
+
+    assert_eq!(code, Some(2));
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().any(|r| r["ruleId"] == "GMS003" && r["fixes"].is_null()));
+}
+
+#[test]
+fn test_check_arity_warn() {
+    let dir = tree(&[("a.h", "MOCK_METHOD2(Foo, bool(int));")]);
+    let (log, code) = log(&["check", "--arity", "warn", "--format", "sarif", dir.path().to_str().unwrap()]);
+    let result = &log["runs"][0]["results"][0];
+
+    assert_eq!(code, Some(1));
+    assert_eq!(result["message"]["text"], "MOCK_METHOD2 can be replaced with MOCK_METHOD");
+    assert_eq!(result["fixes"][0]["artifactChanges"][0]["replacements"][0]["insertedContent"]["text"],
+               "MOCK_METHOD(bool, Foo, (int));");
+}
+
+#[test]
+fn test_clean() {
+    let dir = tree(&[("a.h", "MOCK_METHOD(bool, Foo, (int));")]);
+    let (log, code) = log(&["check", "--format", "sarif", dir.path().to_str().unwrap()]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(log["runs"][0]["results"], Value::Array(vec![]));
+}
+
+#[test]
+fn test_replace_rejects_sarif() {
+    binary().args(["replace", "--format", "sarif", "."])
+            .assert()
+            .failure();
+}
+
+}
@@ -1,6 +1,6 @@
-/// MOCK_METHOD needs brackets to replace prior knowledge from n-suffix
-///
-/// https://github.com/google/googletest/blob/master/googlemock/docs/cook_book.md#dealing-with-unprotected-commas
+//! MOCK_METHOD needs brackets to replace prior knowledge from n-suffix
+//!
+//! https://github.com/google/googletest/blob/master/googlemock/docs/cook_book.md#dealing-with-unprotected-commas
 
 mod common;
 use common::*;
@@ -54,4 +54,32 @@ MOCK_METHOD(bool, CheckMap, (
 "
 );
 
+unprotected_commas_test!(
+test_nested_templates
+"MOCK_METHOD2(Store, void(std::map<int, std::vector<int>>, int));"
+->
+"MOCK_METHOD(void, Store, ((std::map<int, std::vector<int>>), int));"
+);
+
+unprotected_commas_test!(
+test_commas_in_parentheses_are_protected
+"MOCK_METHOD2(Call, std::function<void(int, int)>(std::function<bool(int, int)>, int));"
+->
+"MOCK_METHOD(std::function<void(int, int)>, Call, (std::function<bool(int, int)>, int));"
+);
+
+unprotected_commas_test!(
+test_comparison_in_template_argument
+"MOCK_METHOD2(Get, void(Foo<(A > B), C>, int));"
+->
+"MOCK_METHOD(void, Get, ((Foo<(A > B), C>), int));"
+);
+
+unprotected_commas_test!(
+test_braced_template_argument
+"MOCK_METHOD2(Get, void(Foo<Bar{1, 2}>, std::array<int, N>));"
+->
+"MOCK_METHOD(void, Get, ((Foo<Bar{1, 2}>), (std::array<int, N>)));"
+);
+
 }
@@ -1,8 +1,8 @@
-/// Non-obvious behaviour with void keyword
-///
-/// valid-old-style: MOCK_METHOD0(Foo, bool(void))
-/// naive-new-style: MOCK_METHOD(bool, Foo, (void))  // Error
-/// valid-new-style: MOCK_METHOD(bool, Foo, ())
+//! Non-obvious behaviour with void keyword
+//!
+//! valid-old-style: MOCK_METHOD0(Foo, bool(void))
+//! naive-new-style: MOCK_METHOD(bool, Foo, (void))  // Error
+//! valid-new-style: MOCK_METHOD(bool, Foo, ())
 
 mod common;
 use common::*;
@@ -54,11 +54,10 @@ test_old_style_void_args_is_empty_in_new_style_whitespace
 );
 
 void_test!(
-ignore // Aware of scenario. Could capture n from MOCK_METHODn
 test_old_style_void_args_is_empty_in_new_style_inline_comments
 "MOCK_METHOD0(Foo, bool(/* Some */ void /* comments */))"
 ->
-"MOCK_METHOD(bool, Foo, ())"
+"MOCK_METHOD(bool, Foo, (/* Some */ /* comments */))"
 );
 
 }